@@ -1,9 +1,9 @@
 use bevy::{
+    math::Affine2,
     prelude::*,
     sprite::{Anchor, MaterialMesh2dBundle},
 };
 use bevy_asset_loader::prelude::*;
-use bevy_mouse_position::{MousePositionPlugin, WorldPosition};
 
 const WINDOW_TITLE: &str = "Doodle Demigod";
 const WINDOW_WIDTH: f32 = 1133.0;
@@ -94,7 +94,8 @@ fn main() {
             },
             ..default()
         }))
-        .add_plugin(MousePositionPlugin)
+        .init_resource::<WorldPosition>()
+        .add_system(track_world_position)
         .insert_resource(ClearColor(BACKGROUND_COLOR))
         .add_startup_system(spawn_camera)
         .add_plugin(DoodleDemiGodPlugin)
@@ -102,6 +103,22 @@ fn main() {
         .run();
 }
 
+#[derive(Resource, Default)]
+struct WorldPosition(Vec2);
+
+fn track_world_position(
+    windows: Res<Windows>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut world_position: ResMut<WorldPosition>,
+) {
+    let (camera, transform) = cameras.single();
+    let cursor = windows.get_primary().and_then(|w| w.cursor_position());
+
+    if let Some(ray) = cursor.and_then(|c| camera.viewport_to_world(transform, c)) {
+        world_position.0 = ray.origin.truncate();
+    }
+}
+
 struct DoodleDemiGodPlugin;
 
 impl Plugin for DoodleDemiGodPlugin {
@@ -126,7 +143,6 @@ impl Plugin for DoodleDemiGodPlugin {
         .add_system_set(
             SystemSet::on_update(GameState::Next)
                 .with_system(reposition_tile_choices.label("tile_reposition"))
-                .with_system(update_bounds_position.after("tile_reposition"))
                 .with_system(hover_square)
                 .with_system(select_tile.label("select_title"))
                 .with_system(deselect_tile)
@@ -171,48 +187,31 @@ struct Recipes(Vec<Recipe>);
 
 impl Recipes {
     fn find_by_tiles(&self, tiles: [Option<TileType>; 2]) -> Option<TileType> {
-        let item1 = tiles.get(0).unwrap().unwrap();
+        let item1 = tiles.first().unwrap().unwrap();
         let item2 = tiles.get(1).unwrap().unwrap();
 
         let recipe = self.0.iter().find(|r| {
             let first_item_in = r.ingredients.contains(&item1);
             let second_item_in = r.ingredients.contains(&item2);
 
-            return first_item_in && second_item_in;
+            first_item_in && second_item_in
         });
 
-        match recipe {
-            Some(r) => Some(r.result),
-            _ => None,
-        }
+        recipe.map(|r| r.result)
     }
 }
 
 impl Default for Recipes {
     fn default() -> Self {
-        let mut recipes: Vec<Recipe> = vec![];
-
-        recipes.push(Recipe::new(
-            [TileType::Trees, TileType::Rocks],
-            TileType::TreesRocks,
-        ));
-
-        recipes.push(Recipe::new(
-            [TileType::Stone, TileType::Trees],
-            TileType::StoneTrees,
-        ));
-
-        recipes.push(Recipe::new(
-            [TileType::Rocks, TileType::TreesRocks],
-            TileType::Fountain,
-        ));
-
-        recipes.push(Recipe::new(
-            [TileType::Fountain, TileType::StoneTrees],
-            TileType::FountainStoneTrees,
-        ));
-
-        Recipes(recipes)
+        Recipes(vec![
+            Recipe::new([TileType::Trees, TileType::Rocks], TileType::TreesRocks),
+            Recipe::new([TileType::Stone, TileType::Trees], TileType::StoneTrees),
+            Recipe::new([TileType::Rocks, TileType::TreesRocks], TileType::Fountain),
+            Recipe::new(
+                [TileType::Fountain, TileType::StoneTrees],
+                TileType::FountainStoneTrees,
+            ),
+        ])
     }
 }
 
@@ -223,9 +222,8 @@ struct SlotBorderColor(Color);
 struct Slot(Option<TileType>);
 
 impl Slot {
-    fn all_slots_occupied(slots: &Vec<&Slot>) -> bool {
-        let empty_slot = slots.iter().find(|s| s.0 == None);
-        return empty_slot == None;
+    fn all_slots_occupied(slots: &[&Slot]) -> bool {
+        slots.iter().all(|s| s.0.is_some())
     }
 }
 
@@ -245,34 +243,32 @@ impl Tile {
 }
 
 #[derive(Component)]
-struct TileContainer(Vec2);
-
-#[derive(Component)]
-struct Hovered;
+struct TileContainer;
 
+/// Same as the kitchen sink's `OrientedBounds2`, a rectangle in the entity's local space.
+/// Hit testing goes through the `GlobalTransform`, so the hit area grows with the hover
+/// scale instead of staying at the unscaled size
 #[derive(Debug, Clone, Copy, Component)]
-pub struct Bounds2 {
-    pub position: Vec2,
+pub struct OrientedBounds2 {
     pub size: Vec2,
 }
 
-impl Bounds2 {
-    pub fn in_bounds(&self, coords: Vec2) -> bool {
-        coords.x >= self.position.x
-            && coords.y >= self.position.y
-            && coords.x <= self.position.x + self.size.x
-            && coords.y <= self.position.y + self.size.y
+impl OrientedBounds2 {
+    pub fn new(size: Vec2) -> Self {
+        OrientedBounds2 { size }
     }
 
-    pub fn in_bounds_centered(&self, coords: Vec2) -> bool {
-        let half_size = self.size * Vec2::new(0.5, 0.5);
-        let new_position = self.position - half_size;
-        let bounds = Bounds2 {
-            position: new_position,
-            size: self.size,
-        };
+    pub fn in_bounds(&self, transform: &GlobalTransform, coords: Vec2) -> bool {
+        let affine = transform.affine();
+        let local_to_world = Affine2::from_cols(
+            affine.matrix3.x_axis.truncate(),
+            affine.matrix3.y_axis.truncate(),
+            affine.translation.truncate(),
+        );
+        let local = local_to_world.inverse().transform_point2(coords);
+        let half_size = self.size * 0.5;
 
-        return bounds.in_bounds(coords);
+        local.x.abs() <= half_size.x && local.y.abs() <= half_size.y
     }
 }
 
@@ -361,10 +357,10 @@ fn spawn_initial_tiles(
                 transform: Transform::from_xyz(-half_width + 20.0, half_height - 20.0, 0.0),
                 ..default()
             },
-            TileContainer(size),
+            TileContainer,
         ))
         .with_children(|parent| {
-            vec![TileType::Trees, TileType::Rocks, TileType::Stone]
+            [TileType::Trees, TileType::Rocks, TileType::Stone]
                 .iter()
                 .for_each(|tile| {
                     parent.spawn((
@@ -373,12 +369,9 @@ fn spawn_initial_tiles(
                             sprite: Sprite { ..default() },
                             ..default()
                         },
-                        Tile(tile.clone()),
+                        Tile(*tile),
                         Name::new(tile.name()),
-                        Bounds2 {
-                            position: Vec2::default(),
-                            size: Vec2::new(TILE_WIDTH, TILE_HEIGHT),
-                        },
+                        OrientedBounds2::new(Vec2::new(TILE_WIDTH, TILE_HEIGHT)),
                     ));
                 });
         });
@@ -447,18 +440,12 @@ fn reposition_tile_choices(
     }
 }
 
-fn update_bounds_position(
-    mut tiles: Query<(&GlobalTransform, &mut Bounds2), Or<(With<Tile>, With<GoalTranslation>)>>,
+fn hover_square(
+    mut query: Query<(&mut Transform, &GlobalTransform, &OrientedBounds2)>,
+    mouse_position: Res<WorldPosition>,
 ) {
-    for (transform, mut bounds) in tiles.iter_mut() {
-        let t = transform.translation();
-        bounds.position = Vec2::new(t.x, t.y);
-    }
-}
-
-fn hover_square(mut query: Query<(&mut Transform, &Bounds2)>, mouse_position: Res<WorldPosition>) {
-    for (mut transform, bounds) in query.iter_mut() {
-        if bounds.in_bounds_centered(mouse_position.0) {
+    for (mut transform, global_transform, bounds) in query.iter_mut() {
+        if bounds.in_bounds(global_transform, mouse_position.0) {
             transform.scale = transform.scale.lerp(Vec3::new(1.15, 1.15, 1.1), 0.2);
         } else {
             transform.scale = Vec3::new(1.0, 1.0, 1.0);
@@ -469,7 +456,7 @@ fn hover_square(mut query: Query<(&mut Transform, &Bounds2)>, mouse_position: Re
 fn select_tile(
     mut commands: Commands,
     buttons: Res<Input<MouseButton>>,
-    tile_query: Query<(&Tile, &Bounds2, &GlobalTransform)>,
+    tile_query: Query<(&Tile, &OrientedBounds2, &GlobalTransform)>,
     mut slots_query: Query<(Entity, &mut Slot, &GlobalTransform)>,
     mouse_position: Res<WorldPosition>,
     tile_assets: Res<TileAssets>,
@@ -478,21 +465,21 @@ fn select_tile(
     if buttons.just_pressed(MouseButton::Left) {
         let tile = tile_query
             .iter()
-            .filter(|(_, bounds, _)| bounds.in_bounds_centered(mouse_position.0))
+            .filter(|(_, bounds, transform)| bounds.in_bounds(transform, mouse_position.0))
             .collect::<Vec<_>>();
 
-        if let Some(tile) = tile.get(0) {
+        if let Some(tile) = tile.first() {
             let c_tile = tile.0;
             let tile_transform = tile.2;
 
             for (entity, mut slot, transform) in slots_query.iter_mut() {
-                if slot.0 != None {
+                if slot.0.is_some() {
                     continue;
                 }
 
                 slot.0 = Some(c_tile.0);
 
-                let mut goal: Vec3 = Vec3::from(transform.translation());
+                let mut goal: Vec3 = transform.translation();
                 goal.z = 2.0;
                 let sprite_transform = Transform::from_xyz(
                     tile_transform.translation().x,
@@ -508,10 +495,7 @@ fn select_tile(
                     },
                     BelongsTo(entity),
                     GoalTranslation(goal),
-                    Bounds2 {
-                        position: Vec2::default(),
-                        size: Vec2::new(TILE_WIDTH, TILE_HEIGHT),
-                    },
+                    OrientedBounds2::new(Vec2::new(TILE_WIDTH, TILE_HEIGHT)),
                 ));
 
                 ev_tile_selected.send(TileSelectEvent);
@@ -526,18 +510,18 @@ fn deselect_tile(
     mut commands: Commands,
     buttons: Res<Input<MouseButton>>,
     mut slot_query: Query<(Entity, &mut Slot)>,
-    tile_query: Query<(Entity, &Bounds2, &BelongsTo)>,
+    tile_query: Query<(Entity, &OrientedBounds2, &GlobalTransform, &BelongsTo)>,
     mouse_position: Res<WorldPosition>,
 ) {
     if buttons.just_pressed(MouseButton::Left) {
         let tile = tile_query
             .iter()
-            .filter(|(_, bounds, _)| bounds.in_bounds_centered(mouse_position.0))
+            .filter(|(_, bounds, transform, _)| bounds.in_bounds(transform, mouse_position.0))
             .collect::<Vec<_>>();
 
-        if let Some(tile) = tile.get(0) {
+        if let Some(tile) = tile.first() {
             let tile_entity = tile.0;
-            let tile_belongs_to = tile.2;
+            let tile_belongs_to = tile.3;
 
             for (slot_entity, mut slot) in slot_query.iter_mut() {
                 if slot_entity != tile_belongs_to.0 {
@@ -591,7 +575,7 @@ fn spawn_recipe_tile(
 
     let tile = ev_recipe_tile.iter().collect::<Vec<_>>();
 
-    if let Some(tile) = tile.get(0) {
+    if let Some(tile) = tile.first() {
         let tile = tile.0;
         let parent = tile_container.single();
         let child = commands
@@ -601,12 +585,9 @@ fn spawn_recipe_tile(
                     sprite: Sprite { ..default() },
                     ..default()
                 },
-                Tile(tile.clone()),
+                Tile(tile),
                 Name::new(tile.name()),
-                Bounds2 {
-                    position: Vec2::default(),
-                    size: Vec2::new(TILE_WIDTH, TILE_HEIGHT),
-                },
+                OrientedBounds2::new(Vec2::new(TILE_WIDTH, TILE_HEIGHT)),
             ))
             .id();
 
@@ -616,11 +597,10 @@ fn spawn_recipe_tile(
     ev_recipe_tile.clear()
 }
 
+type MergeButton<'a> = (&'a Interaction, &'a mut BackgroundColor, &'a mut Transform);
+
 fn check_merge(
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor, &mut Transform),
-        (Changed<Interaction>, With<Button>),
-    >,
+    mut interaction_query: Query<MergeButton, (Changed<Interaction>, With<Button>)>,
     mut ev_clear_slots: EventWriter<ClearSlotsEvent>,
     mut ev_spawn_recipe_tile: EventWriter<SpawnRecipeTileEvent>,
     slots: Query<&mut Slot>,
@@ -636,8 +616,8 @@ fn check_merge(
                 let all_slots = &slots.iter().collect::<Vec<_>>();
                 let all_tiles = tiles.iter().collect::<Vec<_>>();
 
-                if Slot::all_slots_occupied(&all_slots) {
-                    let s1 = all_slots.get(0).unwrap();
+                if Slot::all_slots_occupied(all_slots) {
+                    let s1 = all_slots.first().unwrap();
                     let s2 = all_slots.get(1).unwrap();
                     let tiles: [Option<TileType>; 2] = [s1.0, s2.0];
                     let recipe = recipes.find_by_tiles(tiles);
@@ -33,5 +33,5 @@ fn main() {
 struct LockPickingPlugin;
 
 impl Plugin for LockPickingPlugin {
-    fn build(&self, app: &mut App) {}
+    fn build(&self, _app: &mut App) {}
 }
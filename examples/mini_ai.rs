@@ -117,7 +117,7 @@ impl Mine {
 struct Worker;
 
 #[derive(Component)]
-struct WorkingZone(Entity);
+struct WorkingZone;
 
 impl Worker {
     fn body() -> RegularPolygon {
@@ -159,7 +159,7 @@ impl Worker {
             .filter(|(_, spot)| !spot.occupied)
             .collect::<Vec<_>>();
 
-        if filtered_spots.len() == 0 {
+        if filtered_spots.is_empty() {
             return;
         }
 
@@ -169,9 +169,9 @@ impl Worker {
             let chosen_spot = filtered_spots.pop();
 
             match chosen_spot {
-                Some((spot_id, mut spot)) => {
+                Some((_, mut spot)) => {
                     spot.occupied = true;
-                    commands.entity(worker_id).insert(WorkingZone(spot_id));
+                    commands.entity(worker_id).insert(WorkingZone);
                 }
                 None => println!("Invalid Spot"),
             }
@@ -85,12 +85,8 @@ fn determine_tile_type(value: &f32) -> &str {
 }
 
 fn draw_map_grid(mut commands: Commands, asset_server: Res<AssetServer>, atlas: Res<MapAtlas>) {
-    let mut x = 0;
-
-    for row in &atlas.0 {
-        let mut y = 0;
-
-        for col in row {
+    for (x, row) in atlas.0.iter().enumerate() {
+        for (y, col) in row.iter().enumerate() {
             commands.spawn((
                 build_tile(
                     Vec2::new(
@@ -98,13 +94,10 @@ fn draw_map_grid(mut commands: Commands, asset_server: Res<AssetServer>, atlas:
                         TILE_SIZE * y as f32 - TILE_OFFSET,
                     ),
                     &asset_server,
-                    determine_tile_type(&col),
+                    determine_tile_type(col),
                 ),
                 Tile,
             ));
-
-            y += 1;
         }
-        x += 1;
     }
 }
@@ -239,10 +239,10 @@ fn gen_tile_list() -> Vec<Tile> {
     let curve_trc = TileBuilder::new().left().bottom().build(TileType::CurveTrc);
 
     // TODO: Confirm end_t and end_b
-    let end_t = TileBuilder::new().bottom().build(TileType::EndT);
-    let end_b = TileBuilder::new().top().build(TileType::EndB);
-    let end_r = TileBuilder::new().right().build(TileType::EndR);
-    let end_l = TileBuilder::new().left().build(TileType::EndL);
+    // let end_t = TileBuilder::new().bottom().build(TileType::EndT);
+    // let end_b = TileBuilder::new().top().build(TileType::EndB);
+    // let end_r = TileBuilder::new().right().build(TileType::EndR);
+    // let end_l = TileBuilder::new().left().build(TileType::EndL);
 
     let l_to_r = TileBuilder::new()
         .right()
@@ -407,7 +407,7 @@ impl WaveCollapse {
     }
 
     fn collapse_cell(&mut self, at: &Pos) {
-        let cell = self.get_cell(&at);
+        let cell = self.get_cell(at);
         // dbg!(&cell);
         let chosen_tile = cell.choose(&mut rand::thread_rng());
 
@@ -418,6 +418,8 @@ impl WaveCollapse {
         }
     }
 
+    // Only the tests pick by lowest entropy, `collapse` walks the grid in order
+    #[allow(dead_code)]
     fn get_next_lowest_tile(&self) -> Pos {
         let mut pos = Pos { row: 0, col: 0 };
         let mut count = usize::MAX;
@@ -469,33 +471,29 @@ impl WaveCollapse {
     }
 
     fn propogate(&mut self, from: &Pos, direction: OffsetType) {
-        let tile = self.get_collapsed_cell(&from);
+        let tile = self.get_collapsed_cell(from);
 
         if let Some(tile) = tile {
-            let offset_tile = self.get_offset_cell(&from, direction);
+            let offset_tile = self.get_offset_cell(from, direction);
 
             if let Some(offset_tile) = offset_tile {
-                offset_tile.retain(|t| {
-                    let result = match direction {
-                        OffsetType::Top => {
-                            tile.top.contains(&t.of_type)
-                                || (tile.top.len() == 0 && t.bottom.len() == 0)
-                        }
-                        OffsetType::Right => {
-                            tile.right.contains(&t.of_type)
-                                || (tile.right.len() == 0 && t.left.len() == 0)
-                        }
-                        OffsetType::Bottom => {
-                            tile.bottom.contains(&t.of_type)
-                                || (tile.bottom.len() == 0 && t.top.len() == 0)
-                        }
-                        OffsetType::Left => {
-                            tile.left.contains(&t.of_type)
-                                || (tile.left.len() == 0 && t.right.len() == 0)
-                        }
-                    };
-
-                    result
+                offset_tile.retain(|t| match direction {
+                    OffsetType::Top => {
+                        tile.top.contains(&t.of_type)
+                            || (tile.top.is_empty() && t.bottom.is_empty())
+                    }
+                    OffsetType::Right => {
+                        tile.right.contains(&t.of_type)
+                            || (tile.right.is_empty() && t.left.is_empty())
+                    }
+                    OffsetType::Bottom => {
+                        tile.bottom.contains(&t.of_type)
+                            || (tile.bottom.is_empty() && t.top.is_empty())
+                    }
+                    OffsetType::Left => {
+                        tile.left.contains(&t.of_type)
+                            || (tile.left.is_empty() && t.right.is_empty())
+                    }
                 });
             }
         }
@@ -590,11 +588,12 @@ mod tests {
     fn test_propogate() {
         let mut wave = WaveCollapse::new(5, 5);
         let pos = Pos { row: 1, col: 1 };
-        wave.collapse_cell(&pos);
+        // The cross connects on every side, everything with a bottom path can sit on top
+        wave.collapsed_grid[pos.row][pos.col] = Some(gen_tile_list()[0].clone());
         wave.propogate(&pos, OffsetType::Top);
         let top_tile = wave.get_offset_cell(&pos, OffsetType::Top);
 
-        assert_eq!(10, top_tile.unwrap().len());
+        assert_eq!(9, top_tile.unwrap().len());
     }
 
     #[test]
@@ -693,19 +692,16 @@ fn spawn_grid_map(mut commands: Commands, assets: Res<TileAssets>) {
                 let mut sprite_bundle = SpriteBundle {
                     transform: Transform::from_xyz(
                         (TILE_SIZE * j as f32) - TILE_OFFSET,
-                        (TILE_SIZE * i as f32 * -1.0) + TILE_OFFSET,
+                        -(TILE_SIZE * i as f32) + TILE_OFFSET,
                         0.0,
                     ),
                     ..default()
                 };
                 let asset = tile.of_type.asset(&assets);
 
-                match asset {
-                    Some(texture) => {
-                        sprite_bundle.texture = texture;
-                    }
-                    None => {}
-                };
+                if let Some(texture) = asset {
+                    sprite_bundle.texture = texture;
+                }
 
                 commands.spawn((
                     tile.clone(),
@@ -1,27 +1,452 @@
-use bevy::prelude::{Component, Vec2};
+use bevy::{
+    math::Affine2,
+    prelude::{Component, GlobalTransform, Vec2},
+};
 
-#[derive(Debug, Clone, Copy, Component)]
+/// Axis aligned rectangle in world space.
+///
+/// `position` is always the center of the rectangle, use `from_corners` when
+/// working from a corner (eg. a drag selection).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Component)]
 pub struct Bounds2 {
     pub position: Vec2,
     pub size: Vec2,
 }
 
 impl Bounds2 {
+    /// Builds bounds from any two opposite corners
+    pub fn from_corners(a: Vec2, b: Vec2) -> Self {
+        let min = a.min(b);
+        let max = a.max(b);
+
+        Bounds2 {
+            position: (min + max) * 0.5,
+            size: max - min,
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn half_size(&self) -> Vec2 {
+        self.size * 0.5
+    }
+
+    /// Bottom left corner
+    pub fn min(&self) -> Vec2 {
+        self.position - self.half_size()
+    }
+
+    /// Top right corner
+    pub fn max(&self) -> Vec2 {
+        self.position + self.half_size()
+    }
+
+    pub fn top_left(&self) -> Vec2 {
+        Vec2::new(self.min().x, self.max().y)
+    }
+
+    pub fn top_right(&self) -> Vec2 {
+        self.max()
+    }
+
+    pub fn bottom_left(&self) -> Vec2 {
+        self.min()
+    }
+
+    pub fn bottom_right(&self) -> Vec2 {
+        Vec2::new(self.max().x, self.min().y)
+    }
+
+    /// Counter-clockwise starting from the bottom left, same as `OrientedBounds2::corners`
+    pub fn corners(&self) -> [Vec2; 4] {
+        [
+            self.bottom_left(),
+            self.bottom_right(),
+            self.top_right(),
+            self.top_left(),
+        ]
+    }
+
     pub fn in_bounds(&self, coords: Vec2) -> bool {
-        coords.x >= self.position.x
-            && coords.y >= self.position.y
-            && coords.x <= self.position.x + self.size.x
-            && coords.y <= self.position.y + self.size.y
+        let min = self.min();
+        let max = self.max();
+
+        coords.x >= min.x && coords.y >= min.y && coords.x <= max.x && coords.y <= max.y
     }
 
+    #[deprecated(note = "`position` is always the center now, use `in_bounds`")]
     pub fn in_bounds_centered(&self, coords: Vec2) -> bool {
-        let half_size = self.size * Vec2::new(0.5, 0.5);
-        let new_position = self.position - half_size;
+        self.in_bounds(coords)
+    }
+
+    /// True if `other` is completely inside these bounds
+    pub fn contains(&self, other: &Bounds2) -> bool {
+        self.min().cmple(other.min()).all() && self.max().cmpge(other.max()).all()
+    }
+
+    /// True if the bounds overlap, touching edges count as overlapping
+    pub fn intersects(&self, other: &Bounds2) -> bool {
+        self.min().cmple(other.max()).all() && self.max().cmpge(other.min()).all()
+    }
+
+    /// The overlapping area of both bounds
+    pub fn intersection(&self, other: &Bounds2) -> Option<Bounds2> {
+        if !self.intersects(other) {
+            return None;
+        }
+
+        Some(Bounds2::from_corners(
+            self.min().max(other.min()),
+            self.max().min(other.max()),
+        ))
+    }
+
+    /// The smallest bounds containing both
+    pub fn union(&self, other: &Bounds2) -> Bounds2 {
+        Bounds2::from_corners(self.min().min(other.min()), self.max().max(other.max()))
+    }
+
+    /// Grows each side by `amount`, the center stays put
+    pub fn expand(&self, amount: Vec2) -> Bounds2 {
+        Bounds2 {
+            position: self.position,
+            size: (self.size + amount * 2.0).max(Vec2::ZERO),
+        }
+    }
+
+    /// Shrinks each side by `amount`, won't go below a size of zero
+    pub fn shrink(&self, amount: Vec2) -> Bounds2 {
+        self.expand(-amount)
+    }
+
+    /// The closest point inside the bounds
+    pub fn clamp_point(&self, point: Vec2) -> Vec2 {
+        point.clamp(self.min(), self.max())
+    }
+
+    /// Distance from the edge of the bounds, 0 when the point is inside
+    pub fn distance_to_point(&self, point: Vec2) -> f32 {
+        self.clamp_point(point).distance(point)
+    }
+}
+
+/// A rectangle that lives in the entity's local space.
+///
+/// Unlike `Bounds2` there is no position to keep in sync, the entity's `GlobalTransform`
+/// is used when hit testing so rotation, (non-uniform) scale and any parent transforms
+/// are all taken into account.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct OrientedBounds2 {
+    /// Size of the rectangle before any scaling is applied
+    pub size: Vec2,
+    /// Offset of the rectangle's center from the entity's origin, in local space
+    pub offset: Vec2,
+}
+
+impl OrientedBounds2 {
+    pub fn new(size: Vec2) -> Self {
+        OrientedBounds2 {
+            size,
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn in_bounds(&self, transform: &GlobalTransform, coords: Vec2) -> bool {
+        let local = world_to_local(transform).transform_point2(coords) - self.offset;
+        let half_size = self.size * 0.5;
+
+        // A degenerate transform (zero scale) produces NaN which fails both checks
+        local.x.abs() <= half_size.x && local.y.abs() <= half_size.y
+    }
+
+    /// World space corners, counter-clockwise starting from the bottom left
+    pub fn corners(&self, transform: &GlobalTransform) -> [Vec2; 4] {
+        let half_size = self.size * 0.5;
+        let local_to_world = local_to_world(transform);
+
+        [
+            Vec2::new(-half_size.x, -half_size.y),
+            Vec2::new(half_size.x, -half_size.y),
+            Vec2::new(half_size.x, half_size.y),
+            Vec2::new(-half_size.x, half_size.y),
+        ]
+        .map(|corner| local_to_world.transform_point2(corner + self.offset))
+    }
+
+    /// The smallest axis aligned `Bounds2` that contains the rotated rectangle
+    pub fn aabb(&self, transform: &GlobalTransform) -> Bounds2 {
+        let corners = self.corners(transform);
+        let min = corners.iter().fold(corners[0], |min, c| min.min(*c));
+        let max = corners.iter().fold(corners[0], |max, c| max.max(*c));
+
+        Bounds2::from_corners(min, max)
+    }
+}
+
+/// Shapes that can be hit tested exactly, defined in local space around the entity's origin
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape2 {
+    /// Full width and height of the rectangle
+    Rectangle(Vec2),
+    /// Radius of the circle
+    Circle(f32),
+    /// Matches `shape::RegularPolygon`, the radius is measured to the corners and the
+    /// first corner points straight up
+    RegularPolygon { radius: f32, sides: usize },
+    /// Vertices can be in either winding order, the polygon must be convex
+    ConvexPolygon(Vec<Vec2>),
+}
+
+impl Shape2 {
+    pub fn hexagon(radius: f32) -> Self {
+        Shape2::RegularPolygon { radius, sides: 6 }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Shape2::Rectangle(size) => {
+                let half_size = *size * 0.5;
+                point.x.abs() <= half_size.x && point.y.abs() <= half_size.y
+            }
+            Shape2::Circle(radius) => point.length_squared() <= radius * radius,
+            Shape2::RegularPolygon { radius, sides } => {
+                convex_polygon_contains(&regular_polygon_vertices(*radius, *sides), point)
+            }
+            Shape2::ConvexPolygon(vertices) => convex_polygon_contains(vertices, point),
+        }
+    }
+
+    /// Half the size of the smallest local space rectangle that contains the shape
+    pub fn half_extents(&self) -> Vec2 {
+        match self {
+            Shape2::Rectangle(size) => *size * 0.5,
+            Shape2::Circle(radius) => Vec2::splat(*radius),
+            Shape2::RegularPolygon { radius, sides } => {
+                polygon_half_extents(&regular_polygon_vertices(*radius, *sides))
+            }
+            Shape2::ConvexPolygon(vertices) => polygon_half_extents(vertices),
+        }
+    }
+}
+
+/// `Shape2` version of `OrientedBounds2`, uses the entity's `GlobalTransform` to hit test
+#[derive(Debug, Clone, Component)]
+pub struct ShapeBounds2(pub Shape2);
+
+impl ShapeBounds2 {
+    pub fn in_bounds(&self, transform: &GlobalTransform, coords: Vec2) -> bool {
+        self.0
+            .contains(world_to_local(transform).transform_point2(coords))
+    }
+
+    /// The smallest axis aligned `Bounds2` that contains the transformed shape
+    pub fn aabb(&self, transform: &GlobalTransform) -> Bounds2 {
+        OrientedBounds2::new(self.0.half_extents() * 2.0).aabb(transform)
+    }
+}
+
+fn regular_polygon_vertices(radius: f32, sides: usize) -> Vec<Vec2> {
+    let step = std::f32::consts::TAU / sides as f32;
+
+    (0..sides)
+        .map(|i| {
+            let theta = std::f32::consts::FRAC_PI_2 - i as f32 * step;
+            Vec2::new(theta.cos(), theta.sin()) * radius
+        })
+        .collect()
+}
+
+fn convex_polygon_contains(vertices: &[Vec2], point: Vec2) -> bool {
+    if vertices.len() < 3 {
+        return false;
+    }
+
+    let mut has_left = false;
+    let mut has_right = false;
+
+    for (index, a) in vertices.iter().enumerate() {
+        let b = vertices[(index + 1) % vertices.len()];
+        let side = (b - *a).perp_dot(point - *a);
+
+        has_left |= side > 0.0;
+        has_right |= side < 0.0;
+
+        // Point is on both sides of the edges, so it has to be outside
+        if has_left && has_right {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn polygon_half_extents(vertices: &[Vec2]) -> Vec2 {
+    vertices
+        .iter()
+        .fold(Vec2::ZERO, |extents, v| extents.max(v.abs()))
+}
+
+/// Flattens a `GlobalTransform` down to the XY plane
+fn local_to_world(transform: &GlobalTransform) -> Affine2 {
+    let affine = transform.affine();
+
+    Affine2::from_cols(
+        affine.matrix3.x_axis.truncate(),
+        affine.matrix3.y_axis.truncate(),
+        affine.translation.truncate(),
+    )
+}
+
+fn world_to_local(transform: &GlobalTransform) -> Affine2 {
+    local_to_world(transform).inverse()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use bevy::prelude::{Quat, Transform, Vec3};
+
+    use super::*;
+
+    fn rotated(x: f32, y: f32, angle: f32) -> GlobalTransform {
+        GlobalTransform::from(
+            Transform::from_xyz(x, y, 0.0).with_rotation(Quat::from_rotation_z(angle)),
+        )
+    }
+
+    fn assert_near(a: Vec2, b: Vec2) {
+        assert!(a.abs_diff_eq(b, 1e-4), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn position_is_the_center() {
         let bounds = Bounds2 {
-            position: new_position,
-            size: self.size,
+            position: Vec2::new(10.0, 10.0),
+            size: Vec2::new(4.0, 2.0),
         };
 
-        return bounds.in_bounds(coords);
+        assert_eq!(bounds.min(), Vec2::new(8.0, 9.0));
+        assert_eq!(bounds.max(), Vec2::new(12.0, 11.0));
+        assert!(bounds.in_bounds(Vec2::new(8.5, 10.5)));
+        assert!(!bounds.in_bounds(Vec2::new(12.5, 10.0)));
+        assert_eq!(
+            Bounds2::from_corners(Vec2::new(12.0, 11.0), Vec2::new(8.0, 9.0)),
+            bounds
+        );
+    }
+
+    #[test]
+    fn oriented_bounds_rotate_with_the_transform() {
+        let bounds = OrientedBounds2::new(Vec2::new(4.0, 2.0));
+        let transform = rotated(10.0, 0.0, FRAC_PI_4);
+
+        // Along the rotated long axis, outside the unrotated box
+        let along = Vec2::new(1.0, 1.0).normalize() * 1.9;
+        assert!(bounds.in_bounds(&transform, Vec2::new(10.0, 0.0) + along));
+        // Inside the unrotated box, outside the rotated one
+        assert!(!bounds.in_bounds(&transform, Vec2::new(11.9, -0.9)));
+
+        let scaled = GlobalTransform::from(Transform::from_scale(Vec3::new(2.0, 2.0, 1.0)));
+        assert!(bounds.in_bounds(&scaled, Vec2::new(3.9, 0.0)));
+        assert!(!bounds.in_bounds(&scaled, Vec2::new(4.1, 0.0)));
+
+        let offset = bounds.with_offset(Vec2::new(2.0, 0.0));
+        assert!(offset.in_bounds(&GlobalTransform::IDENTITY, Vec2::new(3.9, 0.0)));
+        assert!(!offset.in_bounds(&GlobalTransform::IDENTITY, Vec2::new(-0.1, 0.0)));
+    }
+
+    #[test]
+    fn zero_scale_hits_nothing() {
+        let bounds = OrientedBounds2::new(Vec2::splat(4.0));
+        let flat = GlobalTransform::from(Transform::from_scale(Vec3::ZERO));
+
+        assert!(!bounds.in_bounds(&flat, Vec2::ZERO));
+    }
+
+    #[test]
+    fn oriented_aabb_covers_the_rotated_corners() {
+        let bounds = OrientedBounds2::new(Vec2::new(4.0, 2.0));
+        let aabb = bounds.aabb(&rotated(10.0, 0.0, FRAC_PI_4));
+        let half = 3.0 * FRAC_PI_4.cos();
+
+        assert_near(aabb.position, Vec2::new(10.0, 0.0));
+        assert_near(aabb.size, Vec2::splat(half * 2.0));
+    }
+
+    #[test]
+    fn shapes_contain_points() {
+        assert!(Shape2::Rectangle(Vec2::new(4.0, 2.0)).contains(Vec2::new(1.9, 0.9)));
+        assert!(!Shape2::Rectangle(Vec2::new(4.0, 2.0)).contains(Vec2::new(1.9, 1.1)));
+
+        assert!(Shape2::Circle(2.0).contains(Vec2::new(1.4, 1.4)));
+        assert!(!Shape2::Circle(2.0).contains(Vec2::new(1.5, 1.5)));
+
+        // First corner points straight up, the flat sides are left and right
+        let hexagon = Shape2::hexagon(2.0);
+        assert!(hexagon.contains(Vec2::new(0.0, 1.9)));
+        assert!(hexagon.contains(Vec2::new(1.7, 0.0)));
+        assert!(!hexagon.contains(Vec2::new(1.8, 0.0)));
+
+        let triangle = Shape2::ConvexPolygon(vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(2.0, 0.0),
+        ]);
+        assert!(triangle.contains(Vec2::new(0.5, 0.5)));
+        assert!(!triangle.contains(Vec2::new(1.5, 1.5)));
+        assert!(!Shape2::ConvexPolygon(vec![Vec2::ZERO, Vec2::ONE]).contains(Vec2::ZERO));
+    }
+
+    #[test]
+    fn shape_bounds_rotate_with_the_transform() {
+        let bounds = ShapeBounds2(Shape2::Rectangle(Vec2::new(4.0, 2.0)));
+        let transform = rotated(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+
+        assert!(bounds.in_bounds(&transform, Vec2::new(0.0, 1.9)));
+        assert!(!bounds.in_bounds(&transform, Vec2::new(1.9, 0.0)));
+    }
+
+    #[test]
+    fn shape_aabbs() {
+        let at = GlobalTransform::from_xyz(10.0, 5.0, 0.0);
+        let aabb = |shape: Shape2| ShapeBounds2(shape).aabb(&at);
+
+        let rectangle = aabb(Shape2::Rectangle(Vec2::new(4.0, 2.0)));
+        assert_near(rectangle.min(), Vec2::new(8.0, 4.0));
+        assert_near(rectangle.max(), Vec2::new(12.0, 6.0));
+
+        let circle = aabb(Shape2::Circle(3.0));
+        assert_near(circle.min(), Vec2::new(7.0, 2.0));
+        assert_near(circle.max(), Vec2::new(13.0, 8.0));
+
+        // Pointy top and bottom, flat sides at cos(30°) of the radius
+        let hexagon = aabb(Shape2::hexagon(2.0));
+        let flat = 2.0 * (std::f32::consts::PI / 6.0).cos();
+        assert_near(hexagon.min(), Vec2::new(10.0 - flat, 3.0));
+        assert_near(hexagon.max(), Vec2::new(10.0 + flat, 7.0));
+
+        let polygon = aabb(Shape2::ConvexPolygon(vec![
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(0.0, 3.0),
+            Vec2::new(2.0, -1.0),
+        ]));
+        // Half extents are symmetric around the origin, the box is the furthest vertex
+        assert_near(polygon.min(), Vec2::new(8.0, 2.0));
+        assert_near(polygon.max(), Vec2::new(12.0, 8.0));
+
+        let rotated = ShapeBounds2(Shape2::Rectangle(Vec2::new(4.0, 2.0))).aabb(&rotated(
+            0.0,
+            0.0,
+            std::f32::consts::FRAC_PI_2,
+        ));
+        assert_near(rotated.size, Vec2::new(2.0, 4.0));
     }
 }
@@ -0,0 +1,105 @@
+use bevy::{prelude::*, sprite::Mesh2dHandle, transform::TransformSystem};
+
+use crate::bounds::{Bounds2, OrientedBounds2};
+
+/// Plugin that sizes and positions `Bounds2` & `OrientedBounds2` from the entity's
+/// sprite or 2d mesh, keeping them up to date every frame
+#[derive(Default)]
+pub struct BoundsSyncPlugin;
+
+/// Label to allow ordering against `BoundsSyncPlugin`
+#[derive(SystemLabel)]
+pub struct BoundsSyncSystemLabel;
+
+/// Opt in marker, bounds on entities without it are left alone so hand sized
+/// bounds keep working
+#[derive(Component, Default)]
+pub struct SyncBounds;
+
+type SyncedBounds<'a> = (
+    &'a GlobalTransform,
+    Option<&'a mut Bounds2>,
+    Option<&'a mut OrientedBounds2>,
+);
+
+impl Plugin for BoundsSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            sync_sprite_bounds
+                .label(BoundsSyncSystemLabel)
+                .after(TransformSystem::TransformPropagate),
+        )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            sync_mesh_bounds
+                .label(BoundsSyncSystemLabel)
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+fn sync_sprite_bounds(
+    mut query: Query<(&Sprite, &Handle<Image>, SyncedBounds), With<SyncBounds>>,
+    images: Res<Assets<Image>>,
+) {
+    for (sprite, texture, (transform, bounds, oriented)) in query.iter_mut() {
+        // Without a custom size we need to wait for the texture to load
+        let size = match sprite.custom_size {
+            Some(size) => size,
+            None => match images.get(texture) {
+                Some(image) => image.size(),
+                None => continue,
+            },
+        };
+
+        // Sprites are drawn offset from their origin based on the anchor,
+        // eg. `Anchor::TopLeft` puts the origin in the top left corner
+        let offset = -sprite.anchor.as_vec() * size;
+
+        apply_local_rect(size, offset, transform, bounds, oriented);
+    }
+}
+
+fn sync_mesh_bounds(
+    mut query: Query<(&Mesh2dHandle, SyncedBounds), With<SyncBounds>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for (handle, (transform, bounds, oriented)) in query.iter_mut() {
+        let aabb = match meshes.get(&handle.0).and_then(|mesh| mesh.compute_aabb()) {
+            Some(aabb) => aabb,
+            None => continue,
+        };
+
+        let size = aabb.half_extents.truncate() * 2.0;
+        let offset = aabb.center.truncate();
+
+        apply_local_rect(size, offset, transform, bounds, oriented);
+    }
+}
+
+/// Only writes when something changed so `Changed<Bounds2>` stays meaningful
+fn apply_local_rect(
+    size: Vec2,
+    offset: Vec2,
+    transform: &GlobalTransform,
+    bounds: Option<Mut<Bounds2>>,
+    oriented: Option<Mut<OrientedBounds2>>,
+) {
+    let local = OrientedBounds2 { size, offset };
+
+    if let Some(mut oriented) = oriented {
+        if *oriented != local {
+            *oriented = local;
+        }
+    }
+
+    if let Some(mut bounds) = bounds {
+        // `Bounds2` is axis aligned so use the box around the transformed rectangle
+        let synced = local.aabb(transform);
+
+        if *bounds != synced {
+            *bounds = synced;
+        }
+    }
+}
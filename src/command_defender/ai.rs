@@ -0,0 +1,335 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::{
+    board::{Board, BoardPosition, Unit},
+    game::{self, Enemy, GameState, ATTACK_RANGE},
+    parser::GridCoord,
+    Side, SubmitCommand,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    /// Now and then moves a random enemy
+    Easy,
+    /// Steers enemies around whatever blocks them
+    #[default]
+    Normal,
+    /// Every tick, moves the enemy that gets the most out of it
+    Hard,
+}
+
+impl Difficulty {
+    /// Game ticks between moves
+    fn interval(self) -> u32 {
+        match self {
+            Difficulty::Easy => 3,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 1,
+        }
+    }
+}
+
+/// The computer plays the attacker, typing lane changes like `E3:E4` through the same
+/// parsing and checks as the keyboard. The same seed makes the same choices on the same board
+#[derive(Debug, Clone, Default)]
+pub struct ComputerOpponent {
+    pub difficulty: Difficulty,
+    pub seed: u64,
+}
+
+#[derive(Resource)]
+pub(super) struct Computer {
+    difficulty: Difficulty,
+    rng: StdRng,
+    ticks: u32,
+}
+
+impl Computer {
+    pub(super) fn new(opponent: &ComputerOpponent) -> Self {
+        Self {
+            difficulty: opponent.difficulty,
+            rng: StdRng::seed_from_u64(opponent.seed),
+            ticks: 0,
+        }
+    }
+}
+
+/// An enemy stepping into the row above or below
+#[derive(Debug, Clone, Copy)]
+struct LaneChange {
+    from: GridCoord,
+    to: GridCoord,
+}
+
+/// Runs once per game tick, after the waves spawn
+pub(super) fn computer_turn(
+    state: Res<State<GameState>>,
+    board: Res<Board>,
+    mut computer: ResMut<Computer>,
+    enemies: Query<&BoardPosition, With<Enemy>>,
+    units: Query<&BoardPosition, With<Unit>>,
+    mut ev_submit: EventWriter<SubmitCommand>,
+) {
+    if !game::is_playing(&state) {
+        return;
+    }
+
+    computer.ticks += 1;
+    if !computer
+        .ticks
+        .is_multiple_of(computer.difficulty.interval())
+    {
+        return;
+    }
+
+    // Sorted so the rng sees the same choices in the same order every run
+    let mut positions: Vec<GridCoord> = enemies.iter().map(|position| position.0).collect();
+    positions.sort();
+
+    let is_enemy = |coord: GridCoord| positions.binary_search(&coord).is_ok();
+    let units: Vec<GridCoord> = units.iter().map(|position| position.0).collect();
+
+    let lane_changes: Vec<LaneChange> = positions
+        .iter()
+        .flat_map(|from| {
+            [from.row.checked_sub(1), from.row.checked_add(1)]
+                .into_iter()
+                .flatten()
+                .map(|row| LaneChange {
+                    from: *from,
+                    to: GridCoord::new(from.column, row),
+                })
+        })
+        .filter(|change| board.contains(change.to) && board.occupant(change.to).is_none())
+        .collect();
+
+    let computer = &mut *computer;
+    let rng = &mut computer.rng;
+
+    let chosen = match computer.difficulty {
+        Difficulty::Easy if rng.gen_bool(0.5) => None,
+        Difficulty::Easy => lane_changes.choose(rng).copied(),
+        Difficulty::Normal => {
+            let blocked = |coord: GridCoord| {
+                coord.column > 0
+                    && board
+                        .occupant(GridCoord::new(coord.column - 1, coord.row))
+                        .is_some()
+            };
+
+            let around: Vec<LaneChange> = lane_changes
+                .into_iter()
+                .filter(|change| blocked(change.from) && !blocked(change.to))
+                .collect();
+
+            around.choose(rng).copied()
+        }
+        Difficulty::Hard => {
+            let danger = |coord: GridCoord| danger(&board, &units, &is_enemy, coord);
+
+            let gains: Vec<(LaneChange, i64)> = lane_changes
+                .into_iter()
+                .map(|change| (change, danger(change.from) - danger(change.to)))
+                .filter(|(_, gain)| *gain > 0)
+                .collect();
+
+            let best = gains.iter().map(|(_, gain)| *gain).max();
+            let best: Vec<LaneChange> = gains
+                .into_iter()
+                .filter(|(_, gain)| Some(*gain) == best)
+                .map(|(change, _)| change)
+                .collect();
+
+            best.choose(rng).copied()
+        }
+    };
+
+    if let Some(change) = chosen {
+        let input = format!("{}:{}", change.from, change.to);
+        ev_submit.send(SubmitCommand::new(input, Side::Attacker));
+    }
+}
+
+/// How bad a cell is for an enemy: units that can shoot it, and the defender's pieces
+/// still between it and the base
+fn danger(
+    board: &Board,
+    units: &[GridCoord],
+    is_enemy: &impl Fn(GridCoord) -> bool,
+    coord: GridCoord,
+) -> i64 {
+    let in_range = units
+        .iter()
+        .filter(|unit| game::distance(**unit, coord) <= ATTACK_RANGE)
+        .count();
+
+    let in_the_way = (0..coord.column)
+        .map(|column| GridCoord::new(column, coord.row))
+        .filter(|ahead| board.occupant(*ahead).is_some() && !is_enemy(*ahead))
+        .count();
+
+    (in_range + in_the_way) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{event::ManualEventReader, schedule::ShouldRun};
+
+    use super::*;
+    use crate::command_defender::{game::GameTickStage, CommandDefenderPlugin};
+
+    /// A whole headless game against the computer, ticking once per update
+    fn game(opponent: ComputerOpponent) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(CommandDefenderPlugin {
+                headless: true,
+                history_path: None,
+                wave_seed: Some(3),
+                opponent: Some(opponent),
+                ..default()
+            })
+            .stage(GameTickStage, |stage: &mut SystemStage| {
+                stage.set_run_criteria(|| ShouldRun::Yes)
+            });
+        app
+    }
+
+    /// What the computer typed over `ticks` ticks
+    fn play(app: &mut App, ticks: u32) -> Vec<String> {
+        let mut reader = ManualEventReader::<SubmitCommand>::default();
+        let mut inputs = vec![];
+
+        for _ in 0..ticks {
+            app.update();
+            inputs.extend(
+                reader
+                    .iter(app.world.resource::<Events<SubmitCommand>>())
+                    .filter(|ev| ev.side == Side::Attacker)
+                    .map(|ev| ev.input.clone()),
+            );
+        }
+
+        inputs
+    }
+
+    #[test]
+    fn same_seed_same_commands() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let opponent = ComputerOpponent {
+                difficulty,
+                seed: 42,
+            };
+
+            let first = play(&mut game(opponent.clone()), 60);
+            let second = play(&mut game(opponent), 60);
+
+            assert!(!first.is_empty(), "{:?} never moved", difficulty);
+            assert_eq!(first, second, "{:?}", difficulty);
+        }
+    }
+
+    /// Just the computer on a board set up by hand, `#` are walls and `E` enemies
+    fn board(difficulty: Difficulty, rows: &[&str]) -> App {
+        let mut board = Board::new(rows[0].len() as u32, rows.len() as u32, 10.0);
+        let mut app = App::new();
+
+        for (row, line) in rows.iter().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let coord = GridCoord::new(column as u32, row as u32);
+                let entity = match c {
+                    '#' => app.world.spawn(BoardPosition(coord)).id(),
+                    'E' => app.world.spawn((Enemy, BoardPosition(coord))).id(),
+                    _ => continue,
+                };
+                board.place(entity, coord);
+            }
+        }
+
+        app.insert_resource(board)
+            .insert_resource(State::new(GameState::Playing))
+            .insert_resource(Computer::new(&ComputerOpponent {
+                difficulty,
+                seed: 0,
+            }))
+            .add_event::<SubmitCommand>()
+            .add_system(computer_turn);
+        app
+    }
+
+    /// Commands typed on the tick the difficulty next moves
+    fn next_move(app: &mut App, difficulty: Difficulty) -> Vec<String> {
+        for _ in 0..difficulty.interval() {
+            app.update();
+        }
+
+        app.world
+            .resource_mut::<Events<SubmitCommand>>()
+            .drain()
+            .map(|ev| ev.input)
+            .collect()
+    }
+
+    #[test]
+    fn normal_steers_around_blockers() {
+        let mut app = board(
+            Difficulty::Normal,
+            &[
+                "........", //
+                "..#E....", //
+                "..#.....", //
+                "......E.", //
+            ],
+        );
+
+        // C2 blocks D2 and C3 would block it in D3, G4 has a clear lane
+        assert_eq!(next_move(&mut app, Difficulty::Normal), ["D2:D1"]);
+    }
+
+    #[test]
+    fn normal_leaves_free_lanes_alone() {
+        let mut app = board(
+            Difficulty::Normal,
+            &[
+                "........", //
+                "...E....", //
+                "........", //
+            ],
+        );
+
+        assert!(next_move(&mut app, Difficulty::Normal).is_empty());
+    }
+
+    #[test]
+    fn hard_takes_the_biggest_gain() {
+        let mut app = board(
+            Difficulty::Hard,
+            &[
+                "#.......", //
+                "##.....E", //
+                "........", //
+                "........", //
+                "#.....E.", //
+                "........", //
+            ],
+        );
+
+        // H2 to H3 gets past two walls, H2 to H1 and G5 either way only one
+        assert_eq!(next_move(&mut app, Difficulty::Hard), ["H2:H3"]);
+    }
+
+    #[test]
+    fn hard_stays_put_without_a_gain() {
+        let mut app = board(
+            Difficulty::Hard,
+            &[
+                "#......", //
+                "#.....E", //
+                "#......", //
+            ],
+        );
+
+        assert!(next_move(&mut app, Difficulty::Hard).is_empty());
+    }
+}
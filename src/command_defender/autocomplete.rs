@@ -0,0 +1,389 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use super::{
+    board::{Board, BoardPosition, Unit},
+    console::ConsoleState,
+    input::{CommandFocus, CommandInput},
+    parser::GridCoord,
+    verbs::{ArgKind, VerbRegistry},
+};
+
+/// Most suggestions shown in the dropdown
+const MAX_SUGGESTIONS: usize = 6;
+
+const SUGGESTION_COLOR: Color = Color::GRAY;
+const SELECTED_COLOR: Color = Color::WHITE;
+
+/// Completions for the word under the caret, refreshed whenever the input changes.
+/// Offers verbs, cells and the words a verb's arguments take
+#[derive(Resource, Debug, Default)]
+pub struct Suggestions {
+    pub items: Vec<String>,
+    /// Index into `items` that Tab accepts
+    pub selected: usize,
+    /// Characters of the input the accepted suggestion replaces
+    word: Range<usize>,
+    /// What was typed of the word, used for the ghost text
+    typed: String,
+    /// Ghost text only makes sense when the caret is at the end of the word
+    caret_at_end: bool,
+}
+
+impl Suggestions {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Rest of the selected suggestion after what was already typed
+    pub fn ghost(&self) -> Option<&str> {
+        if !self.caret_at_end {
+            return None;
+        }
+
+        self.items
+            .get(self.selected)
+            .and_then(|item| item.get(self.typed.len()..))
+            .filter(|rest| !rest.is_empty())
+    }
+}
+
+/// Characters that split a command into words
+fn is_separator(c: char) -> bool {
+    c == ':' || c == '-' || c.is_whitespace()
+}
+
+/// Tab accepts the selected suggestion, Up and Down pick one while the dropdown is open.
+/// Runs before history recall and takes the arrow keys so they don't do both
+pub(super) fn accept_suggestion(
+    mut keys: ResMut<Input<KeyCode>>,
+    focus: Res<CommandFocus>,
+    mut suggestions: ResMut<Suggestions>,
+    mut command_input: ResMut<CommandInput>,
+) {
+    if !focus.focused || suggestions.is_empty() {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Tab) {
+        if let Some(item) = suggestions.items.get(suggestions.selected) {
+            command_input.replace(suggestions.word.clone(), item);
+        }
+
+        return;
+    }
+
+    let len = suggestions.items.len();
+
+    if keys.clear_just_pressed(KeyCode::Up) {
+        suggestions.selected = (suggestions.selected + len - 1) % len;
+    }
+    if keys.clear_just_pressed(KeyCode::Down) {
+        suggestions.selected = (suggestions.selected + 1) % len;
+    }
+}
+
+pub(super) fn update_suggestions(
+    command_input: Res<CommandInput>,
+    board: Res<Board>,
+    units: Query<&BoardPosition, With<Unit>>,
+    verbs: Res<VerbRegistry>,
+    console: Res<ConsoleState>,
+    mut suggestions: ResMut<Suggestions>,
+) {
+    if !command_input.is_changed() {
+        return;
+    }
+
+    // The console has its own grammar, nothing here fits it
+    if console.open {
+        *suggestions = Suggestions::default();
+        return;
+    }
+
+    let chars: Vec<char> = command_input.text().chars().collect();
+    let caret = command_input.caret();
+    let start = chars[..caret]
+        .iter()
+        .rposition(|c| is_separator(*c))
+        .map_or(0, |i| i + 1);
+    let typed: String = chars[start..caret]
+        .iter()
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    // Nothing to complete until something is typed, so Up and Down stay with the history
+    if typed.is_empty() {
+        *suggestions = Suggestions::default();
+        return;
+    }
+
+    // The word ends at the next separator, accepting replaces all of it
+    let end = chars[caret..]
+        .iter()
+        .position(|c| is_separator(*c))
+        .map_or(chars.len(), |i| caret + i);
+    let before: String = chars[..start].iter().collect();
+    let words: Vec<&str> = before.split_whitespace().collect();
+    // Which word the caret is in, the second half of a range like `A1-A4` is still one word
+    let index = match before.ends_with([':', '-']) {
+        true => words.len().saturating_sub(1),
+        false => words.len(),
+    };
+
+    let mut unit_cells: Vec<GridCoord> = units.iter().map(|position| position.0).collect();
+    unit_cells.sort_by_key(|coord| (coord.column, coord.row));
+
+    // Own units first, then the rest of the board
+    let any_cell = || {
+        let mut items: Vec<String> = unit_cells.iter().map(|c| c.to_string()).collect();
+        items.extend(
+            board
+                .cells()
+                .filter(|coord| !unit_cells.contains(coord))
+                .map(|coord| coord.to_string()),
+        );
+        items
+    };
+
+    let items: Vec<String> = match (words.first().and_then(|w| verbs.get(w)), index) {
+        // Arguments of a verb follow its spec
+        (Some(verb), index) if index > 0 => match verb.args.get(index - 1).map(|arg| arg.kind) {
+            Some(ArgKind::Coord | ArgKind::Range) => any_cell(),
+            Some(ArgKind::OneOf(options)) => options.iter().map(|o| o.to_string()).collect(),
+            _ => vec![],
+        },
+        // First word, could be a verb or the start of a move
+        (_, 0) if !before.contains(':') => verbs
+            .iter()
+            .map(|verb| verb.name.to_string())
+            .chain(any_cell())
+            .collect(),
+        // Somewhere empty to move to
+        _ if before.contains(':') => board
+            .cells()
+            .filter(|coord| board.occupant(*coord).is_none())
+            .map(|coord| coord.to_string())
+            .collect(),
+        _ => any_cell(),
+    };
+
+    let mut items: Vec<String> = items
+        .into_iter()
+        .filter(|item| item.to_ascii_uppercase().starts_with(&typed))
+        .take(MAX_SUGGESTIONS)
+        .collect();
+
+    // Nothing left to complete when the only match is what's already there
+    if items.len() == 1 && items[0].eq_ignore_ascii_case(&typed) {
+        items.clear();
+    }
+
+    *suggestions = Suggestions {
+        items,
+        selected: 0,
+        word: start..end,
+        typed,
+        caret_at_end: end == caret,
+    };
+}
+
+#[derive(Component)]
+pub(super) struct SuggestionList;
+
+#[derive(Component)]
+pub(super) struct SuggestionText;
+
+/// Dropdown centered under the command bar
+pub(super) fn spawn_suggestion_list(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(50.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Undefined),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    SuggestionList,
+                    NodeBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(6.0)),
+                            ..default()
+                        },
+                        background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                        visibility: Visibility { is_visible: false },
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((SuggestionText, TextBundle::default()));
+                });
+        });
+}
+
+pub(super) fn render_suggestions(
+    asset_server: Res<AssetServer>,
+    suggestions: Res<Suggestions>,
+    mut list_query: Query<&mut Visibility, With<SuggestionList>>,
+    mut text_query: Query<&mut Text, With<SuggestionText>>,
+) {
+    if !suggestions.is_changed() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
+    list_query.single_mut().is_visible = !suggestions.is_empty();
+    text_query.single_mut().sections = suggestions
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let color = match i == suggestions.selected {
+                true => SELECTED_COLOR,
+                false => SUGGESTION_COLOR,
+            };
+
+            TextSection::new(
+                format!("{}\n", item),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 18.0,
+                    color,
+                },
+            )
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_defender::VerbSpec;
+
+    fn app() -> App {
+        let mut registry = VerbRegistry::default();
+        registry.insert(VerbSpec::new("help", "").optional("verb", ArgKind::Coord));
+        registry.insert(
+            VerbSpec::new("build", "")
+                .arg("cell", ArgKind::Coord)
+                .arg("kind", ArgKind::OneOf(&["tower", "wall"])),
+        );
+
+        let mut app = App::new();
+        app.init_resource::<CommandInput>()
+            .init_resource::<CommandFocus>()
+            .init_resource::<ConsoleState>()
+            .init_resource::<Suggestions>()
+            .init_resource::<Input<KeyCode>>()
+            .insert_resource(registry)
+            .add_system(accept_suggestion.before(update_suggestions))
+            .add_system(update_suggestions);
+
+        // A unit in C1, listed before the empty cells
+        let mut board = Board::new(3, 2, 10.0);
+        let unit = app
+            .world
+            .spawn((Unit, BoardPosition(GridCoord::new(2, 0))))
+            .id();
+        board.place(unit, GridCoord::new(2, 0));
+        app.insert_resource(board);
+
+        app
+    }
+
+    fn suggest(app: &mut App, text: &str) -> Vec<String> {
+        app.world.resource_mut::<CommandInput>().set(text);
+        app.update();
+        app.world.resource::<Suggestions>().items.clone()
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+
+        let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+    }
+
+    fn text(app: &App) -> String {
+        app.world.resource::<CommandInput>().text().to_string()
+    }
+
+    #[test]
+    fn completes_verb_names_and_cells() {
+        let mut app = app();
+
+        assert_eq!(suggest(&mut app, "b"), ["build", "B1", "B2"]);
+        assert_eq!(suggest(&mut app, "HE"), ["help"]);
+        // Own units come first
+        assert_eq!(suggest(&mut app, "c"), ["C1", "C2"]);
+        // Nothing to finish
+        assert!(suggest(&mut app, "help").is_empty());
+        assert!(suggest(&mut app, "").is_empty());
+        assert!(suggest(&mut app, "x").is_empty());
+    }
+
+    #[test]
+    fn completes_arguments_by_kind() {
+        let mut app = app();
+
+        // A `Coord` argument takes cells, not verbs
+        assert_eq!(suggest(&mut app, "build b"), ["B1", "B2"]);
+        assert_eq!(suggest(&mut app, "build b2 "), Vec::<String>::new());
+        assert_eq!(suggest(&mut app, "build b2 t"), ["tower"]);
+        assert_eq!(suggest(&mut app, "BUILD B2 W"), ["wall"]);
+        // Past the last argument
+        assert!(suggest(&mut app, "build b2 wall w").is_empty());
+
+        // Moves only go to empty cells
+        assert_eq!(suggest(&mut app, "a1:c"), ["C2"]);
+        assert_eq!(suggest(&mut app, "a1-a2:c"), ["C2"]);
+    }
+
+    #[test]
+    fn tab_accepts_the_selected_suggestion() {
+        let mut app = app();
+
+        suggest(&mut app, "bu");
+        assert_eq!(app.world.resource::<Suggestions>().ghost(), Some("ild"));
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(text(&app), "build");
+
+        suggest(&mut app, "build b");
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.world.resource::<Suggestions>().selected, 1);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(text(&app), "build B2");
+
+        // The whole word under the caret is replaced
+        app.world.resource_mut::<CommandInput>().set("build b2 tw");
+        app.world.resource_mut::<CommandInput>().move_to(10, false);
+        app.update();
+        assert_eq!(app.world.resource::<Suggestions>().items, ["tower"]);
+        assert_eq!(app.world.resource::<Suggestions>().ghost(), None);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(text(&app), "build b2 tower");
+    }
+
+    #[test]
+    fn tab_does_nothing_without_focus() {
+        let mut app = app();
+
+        suggest(&mut app, "bu");
+        app.world.resource_mut::<CommandFocus>().focused = false;
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(text(&app), "bu");
+    }
+}
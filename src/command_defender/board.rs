@@ -0,0 +1,461 @@
+use bevy::{ecs::system::EntityCommands, prelude::*, utils::HashMap};
+
+use crate::{bounds::Bounds2, bounds_sync::SyncBounds, interaction::WorldInteraction};
+
+use super::{
+    game::{Enemy, Health, Reload, UNIT_HEALTH},
+    parser::{GridCoord, GridRange},
+    undo::{BoardAction, Step, UndoHistory},
+    Command, CommandExecuted, CommandIssued, CommandRejected, GridCommand, RejectReason, Side,
+};
+
+/// Seconds a unit takes to slide between cells
+const MOVE_SECONDS: f32 = 0.2;
+
+const CELL_COLORS: [Color; 2] = [Color::rgb(0.16, 0.16, 0.2), Color::rgb(0.2, 0.2, 0.25)];
+const UNIT_COLOR: Color = Color::rgb(0.3, 0.7, 0.9);
+
+/// Grid the game is played on. `A1` is the top left cell, columns go right and rows go down.
+/// The board is centered on the world origin
+#[derive(Resource, Debug)]
+pub struct Board {
+    pub columns: u32,
+    pub rows: u32,
+    /// Width and height of a cell in world units
+    pub cell_size: f32,
+    occupants: HashMap<GridCoord, Entity>,
+    positions: HashMap<Entity, GridCoord>,
+}
+
+impl Board {
+    pub fn new(columns: u32, rows: u32, cell_size: f32) -> Self {
+        Self {
+            columns,
+            rows,
+            cell_size,
+            occupants: HashMap::default(),
+            positions: HashMap::default(),
+        }
+    }
+
+    pub fn contains(&self, coord: GridCoord) -> bool {
+        coord.column < self.columns && coord.row < self.rows
+    }
+
+    pub fn occupant(&self, coord: GridCoord) -> Option<Entity> {
+        self.occupants.get(&coord).copied()
+    }
+
+    pub fn position(&self, entity: Entity) -> Option<GridCoord> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// The part of `range` on the board, `None` when none of it is
+    pub fn clamp(&self, range: GridRange) -> Option<GridRange> {
+        let (min, max) = (range.min(), range.max());
+
+        if !self.contains(min) {
+            return None;
+        }
+
+        Some(GridRange {
+            start: min,
+            end: GridCoord::new(max.column.min(self.columns - 1), max.row.min(self.rows - 1)),
+        })
+    }
+
+    /// Every cell on the board, row by row
+    pub fn cells(&self) -> impl Iterator<Item = GridCoord> {
+        GridRange {
+            start: GridCoord::new(0, 0),
+            end: GridCoord::new(self.columns.saturating_sub(1), self.rows.saturating_sub(1)),
+        }
+        .cells()
+    }
+
+    /// Size of the whole board in world units
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.columns as f32, self.rows as f32) * self.cell_size
+    }
+
+    /// World position of the center of a cell, works for cells off the board too
+    pub fn cell_center(&self, coord: GridCoord) -> Vec2 {
+        let half = Vec2::new(self.columns as f32 - 1.0, self.rows as f32 - 1.0) * 0.5;
+
+        Vec2::new(coord.column as f32 - half.x, half.y - coord.row as f32) * self.cell_size
+    }
+
+    /// Cell under a world position, `None` when it is off the board
+    pub fn coord_at(&self, world: Vec2) -> Option<GridCoord> {
+        let top_left = self.size() * Vec2::new(-0.5, 0.5);
+        let column = ((world.x - top_left.x) / self.cell_size).floor();
+        let row = ((top_left.y - world.y) / self.cell_size).floor();
+
+        if column < 0.0 || row < 0.0 {
+            return None;
+        }
+
+        let coord = GridCoord::new(column as u32, row as u32);
+        self.contains(coord).then_some(coord)
+    }
+
+    /// Moves an entity to a cell, replacing whatever was recorded there
+    pub(super) fn place(&mut self, entity: Entity, coord: GridCoord) {
+        self.vacate(entity);
+        self.occupants.insert(coord, entity);
+        self.positions.insert(entity, coord);
+    }
+
+    pub(super) fn vacate(&mut self, entity: Entity) {
+        if let Some(previous) = self.positions.remove(&entity) {
+            if self.occupants.get(&previous) == Some(&entity) {
+                self.occupants.remove(&previous);
+            }
+        }
+    }
+
+    /// Where every movable entity in `from` ends up, or why the move isn't allowed.
+    /// Entities that can't move stay put and block the cell they are in. Cells being left
+    /// by another entity in the same command are free to move into
+    pub fn plan_move(
+        &self,
+        command: &GridCommand,
+        movable: impl Fn(Entity) -> bool,
+    ) -> Result<Vec<(Entity, GridCoord)>, RejectReason> {
+        let start = command.from.start;
+        let offset = IVec2::new(
+            command.to.column as i32 - start.column as i32,
+            command.to.row as i32 - start.row as i32,
+        );
+
+        let mut moves = vec![];
+
+        for coord in command.from.cells() {
+            if !self.contains(coord) {
+                return Err(RejectReason::OffBoard(coord));
+            }
+
+            let entity = match self.occupant(coord).filter(|e| movable(*e)) {
+                Some(entity) => entity,
+                None => continue,
+            };
+
+            let column = coord.column as i64 + offset.x as i64;
+            let row = coord.row as i64 + offset.y as i64;
+            let target = match (u32::try_from(column), u32::try_from(row)) {
+                (Ok(column), Ok(row)) => GridCoord::new(column, row),
+                _ => return Err(RejectReason::OffBoard(command.to)),
+            };
+
+            if !self.contains(target) {
+                return Err(RejectReason::OffBoard(target));
+            }
+
+            moves.push((entity, target));
+        }
+
+        if moves.is_empty() {
+            return Err(RejectReason::NoUnits(command.from));
+        }
+
+        for (_, target) in moves.iter() {
+            let is_blocked = self
+                .occupant(*target)
+                .is_some_and(|other| !moves.iter().any(|(entity, _)| *entity == other));
+
+            if is_blocked {
+                return Err(RejectReason::Occupied(*target));
+            }
+        }
+
+        Ok(moves)
+    }
+}
+
+/// One of the board's cell sprites, hovering and clicking it goes through `Bounds2`
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BoardCell(pub GridCoord);
+
+/// Cell an entity sits in. Change it to move the entity, the `Board` keeps track of who is where
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardPosition(pub GridCoord);
+
+/// A unit the player can move with commands
+#[derive(Component, Debug, Default)]
+pub struct Unit;
+
+/// Slides an entity between cells after its `BoardPosition` changes
+#[derive(Component)]
+pub(super) struct Moving {
+    from: Vec3,
+    to: Vec3,
+    timer: Timer,
+}
+
+/// Units placed when the board is spawned
+#[derive(Resource)]
+pub(super) struct StartingUnits(pub Vec<GridCoord>);
+
+/// Checkered, so neighbouring cells are told apart
+pub(super) fn cell_color(coord: GridCoord) -> Color {
+    CELL_COLORS[((coord.column + coord.row) % 2) as usize]
+}
+
+/// Draws the cells and their labels
+pub(super) fn spawn_board(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    board: Res<Board>,
+) {
+    let label_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: board.cell_size * 0.4,
+        color: Color::GRAY,
+    };
+    let cell_size = Vec2::splat(board.cell_size);
+
+    for coord in board.cells() {
+        commands.spawn((
+            Name::new(coord.to_string()),
+            BoardCell(coord),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: cell_color(coord),
+                    custom_size: Some(cell_size),
+                    ..default()
+                },
+                transform: Transform::from_translation(board.cell_center(coord).extend(0.0)),
+                ..default()
+            },
+            Bounds2::default(),
+            SyncBounds,
+            WorldInteraction::default(),
+        ));
+    }
+
+    let mut spawn_label = |text: String, position: Vec2| {
+        commands.spawn(Text2dBundle {
+            text: Text::from_section(text, label_style.clone())
+                .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(position.extend(1.0)),
+            ..default()
+        });
+    };
+
+    // Letters above and below, numbers left and right
+    for column in 0..board.columns {
+        let top = board.cell_center(GridCoord::new(column, 0));
+        let bottom = board.cell_center(GridCoord::new(column, board.rows.saturating_sub(1)));
+        let offset = Vec2::new(0.0, board.cell_size);
+
+        spawn_label(GridCoord::column_label(column), top + offset);
+        spawn_label(GridCoord::column_label(column), bottom - offset);
+    }
+
+    for row in 0..board.rows {
+        let left = board.cell_center(GridCoord::new(0, row));
+        let right = board.cell_center(GridCoord::new(board.columns.saturating_sub(1), row));
+        let offset = Vec2::new(board.cell_size, 0.0);
+
+        spawn_label((row + 1).to_string(), left - offset);
+        spawn_label((row + 1).to_string(), right + offset);
+    }
+}
+
+pub(super) fn spawn_starting_units(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    starting_units: Res<StartingUnits>,
+) {
+    for coord in starting_units.0.iter() {
+        spawn_unit(&mut commands, &mut board, *coord);
+    }
+}
+
+/// Spawns a square sprite sitting in a cell, `scale` is its size compared to the cell.
+/// It is placed on the board right away so the next command already sees it there
+pub(super) fn spawn_piece<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    board: &mut Board,
+    coord: GridCoord,
+    color: Color,
+    scale: f32,
+) -> EntityCommands<'w, 's, 'a> {
+    let piece = commands.spawn((
+        BoardPosition(coord),
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(board.cell_size * scale)),
+                ..default()
+            },
+            transform: Transform::from_translation(board.cell_center(coord).extend(2.0)),
+            ..default()
+        },
+    ));
+
+    board.place(piece.id(), coord);
+    piece
+}
+
+pub(super) fn spawn_unit(
+    commands: &mut Commands,
+    board: &mut Board,
+    coord: GridCoord,
+) -> Option<Entity> {
+    if !board.contains(coord) {
+        return None;
+    }
+
+    let unit = spawn_piece(commands, board, coord, UNIT_COLOR, 0.6)
+        .insert((
+            Unit,
+            Health::new(UNIT_HEALTH),
+            Reload(0),
+            Name::new(format!("Unit {}", coord)),
+        ))
+        .id();
+
+    Some(unit)
+}
+
+/// Keeps `Board` in step with `BoardPosition`s. New entities snap to their cell,
+/// moved ones slide over
+pub(super) fn sync_board(
+    mut commands: Commands,
+    mut board: ResMut<Board>,
+    query: Query<
+        (
+            Entity,
+            &BoardPosition,
+            &Transform,
+            ChangeTrackers<BoardPosition>,
+        ),
+        Changed<BoardPosition>,
+    >,
+    removed: RemovedComponents<BoardPosition>,
+) {
+    for entity in removed.iter() {
+        board.vacate(entity);
+    }
+
+    for (entity, position, transform, tracker) in query.iter() {
+        board.place(entity, position.0);
+
+        let to = board
+            .cell_center(position.0)
+            .extend(transform.translation.z);
+
+        if tracker.is_added() || transform.translation == to {
+            commands.entity(entity).remove::<Moving>();
+            continue;
+        }
+
+        commands.entity(entity).insert(Moving {
+            from: transform.translation,
+            to,
+            timer: Timer::from_seconds(MOVE_SECONDS, TimerMode::Once),
+        });
+    }
+}
+
+pub(super) fn animate_moves(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Moving)>,
+) {
+    for (entity, mut transform, mut moving) in query.iter_mut() {
+        moving.timer.tick(time.delta());
+        transform.translation = moving.from.lerp(moving.to, moving.timer.percent());
+
+        if moving.timer.finished() {
+            commands.entity(entity).remove::<Moving>();
+        }
+    }
+}
+
+/// Moves the pieces a command targets, or rejects it when the move isn't allowed.
+/// The defender moves its units anywhere, the attacker moves enemies one lane up or down
+#[allow(clippy::too_many_arguments)]
+pub(super) fn execute_moves(
+    mut board: ResMut<Board>,
+    mut undo: ResMut<UndoHistory>,
+    mut ev_issued: EventReader<CommandIssued>,
+    mut query: Query<&mut BoardPosition>,
+    units: Query<(), With<Unit>>,
+    enemies: Query<(), With<Enemy>>,
+    mut ev_executed: EventWriter<CommandExecuted>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    for ev in ev_issued.iter() {
+        let command = match &ev.command {
+            Command::Move(command) => command,
+            _ => continue,
+        };
+
+        let start = command.from.start;
+        let is_lane_change =
+            command.to.column == start.column && command.to.row.abs_diff(start.row) == 1;
+
+        // Each side only moves its own pieces
+        let plan = match ev.side {
+            Side::Defender => board.plan_move(command, |entity| units.contains(entity)),
+            Side::Attacker if !is_lane_change => Err(RejectReason::LaneChange),
+            Side::Attacker => board.plan_move(command, |entity| enemies.contains(entity)),
+        };
+
+        let moves = match plan {
+            Ok(moves) => moves,
+            Err(reason) => {
+                ev_rejected.send(CommandRejected {
+                    input: ev.input.clone(),
+                    side: ev.side,
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let steps: Vec<Step> = moves
+            .into_iter()
+            .filter_map(|(entity, to)| {
+                let from = board.position(entity)?;
+                Some(Step { entity, from, to })
+            })
+            .collect();
+
+        move_pieces(&mut board, &steps, &mut query);
+
+        // Undo is only for the defender
+        if ev.side == Side::Defender {
+            undo.record(BoardAction::Move(steps));
+        }
+
+        ev_executed.send(CommandExecuted {
+            input: ev.input.clone(),
+            side: ev.side,
+            command: ev.command.clone(),
+            output: None,
+        });
+    }
+}
+
+/// Moves pieces to the `to` cell of their step, the steps should already be checked
+pub(super) fn move_pieces(
+    board: &mut Board,
+    steps: &[Step],
+    query: &mut Query<&mut BoardPosition>,
+) {
+    // Vacate first so pieces swapping cells don't overwrite each other
+    for step in steps {
+        board.vacate(step.entity);
+    }
+
+    for step in steps {
+        board.place(step.entity, step.to);
+
+        if let Ok(mut position) = query.get_mut(step.entity) {
+            position.0 = step.to;
+        }
+    }
+}
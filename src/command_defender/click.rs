@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+
+use crate::interaction::{Clicked, HoverEnter, HoverExit};
+
+use super::{
+    board::{self, BoardCell},
+    console::ConsoleState,
+    input::{CommandFocus, CommandInput},
+    parser::{GridCoord, GridRange},
+    verbs::{ArgKind, VerbRegistry},
+};
+
+const HOVER_COLOR: Color = Color::rgb(0.32, 0.32, 0.42);
+
+/// Lights up the cell under the cursor
+pub(super) fn highlight_cell(
+    mut ev_hover_enter: EventReader<HoverEnter>,
+    mut ev_hover_exit: EventReader<HoverExit>,
+    mut query: Query<(&BoardCell, &mut Sprite)>,
+) {
+    for HoverExit(entity) in ev_hover_exit.iter() {
+        if let Ok((cell, mut sprite)) = query.get_mut(*entity) {
+            sprite.color = board::cell_color(cell.0);
+        }
+    }
+
+    for HoverEnter(entity) in ev_hover_enter.iter() {
+        if let Ok((_, mut sprite)) = query.get_mut(*entity) {
+            sprite.color = HOVER_COLOR;
+        }
+    }
+}
+
+/// Clicking a cell types its coordinate into the command bar while it has focus, unless
+/// the console has it
+pub(super) fn click_to_fill(
+    mut ev_clicked: EventReader<Clicked>,
+    cells: Query<&BoardCell>,
+    console: Res<ConsoleState>,
+    focus: Res<CommandFocus>,
+    verbs: Res<VerbRegistry>,
+    mut command_input: ResMut<CommandInput>,
+) {
+    for ev in ev_clicked.iter() {
+        let cell = match cells.get(ev.entity) {
+            Ok(cell) => cell,
+            Err(_) => continue,
+        };
+
+        if console.open || !focus.focused {
+            continue;
+        }
+
+        let text = fill(command_input.text(), cell.0, &verbs);
+        command_input.set(text);
+    }
+}
+
+/// What the command bar holds after clicking `coord`. The first click is where to move
+/// from and the second where to, a verb gets it in the argument being typed if that takes
+/// a cell. Once the command is complete a click starts a new one, anything else is left
+/// as it is
+fn fill(text: &str, coord: GridCoord, verbs: &VerbRegistry) -> String {
+    let trimmed = text.trim_end();
+    let words: Vec<&str> = trimmed.split_whitespace().collect();
+
+    if trimmed.is_empty() {
+        return coord.to_string();
+    }
+
+    // Start of the last word, it is replaced while it's still being typed
+    let last_start = trimmed.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+
+    if let Some(verb) = words.first().and_then(|word| verbs.get(word)) {
+        let args = &words[1..];
+        let typing = !args.is_empty() && trimmed.len() == text.len();
+        let slot = match typing {
+            true => args.len() - 1,
+            false => args.len(),
+        };
+
+        return match verb.args.get(slot).map(|arg| arg.kind) {
+            Some(ArgKind::Range) if typing && trimmed.ends_with('-') => {
+                format!("{}{}", trimmed, coord)
+            }
+            Some(ArgKind::Coord | ArgKind::Range) if typing => {
+                format!("{}{}", &trimmed[..last_start], coord)
+            }
+            Some(ArgKind::Coord | ArgKind::Range) => format!("{} {}", trimmed, coord),
+            _ if verb.parse(args).is_ok() => coord.to_string(),
+            _ => text.to_string(),
+        };
+    }
+
+    if trimmed.ends_with([':', '-']) {
+        return format!("{}{}", trimmed, coord);
+    }
+
+    // Half typed cell after a `:` or `-`, eg. `B2:C`
+    if let Some(separator) = trimmed.rfind([':', '-']) {
+        if trimmed[separator + 1..].parse::<GridCoord>().is_err() {
+            return format!("{}{}", &trimmed[..=separator], coord);
+        }
+    }
+
+    match !trimmed.contains(':') && trimmed.parse::<GridRange>().is_ok() {
+        true => format!("{}:{}", trimmed, coord),
+        false => coord.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_defender::VerbSpec;
+
+    fn verbs() -> VerbRegistry {
+        let mut verbs = VerbRegistry::default();
+        verbs.insert(VerbSpec::new("help", ""));
+        verbs.insert(
+            VerbSpec::new("build", "")
+                .arg("cell", ArgKind::Coord)
+                .arg("kind", ArgKind::OneOf(&["tower", "wall"])),
+        );
+        verbs.insert(
+            VerbSpec::new("clear", "")
+                .arg("cells", ArgKind::Range)
+                .optional("target", ArgKind::Coord),
+        );
+        verbs
+    }
+
+    fn click(text: &str) -> String {
+        fill(text, GridCoord::new(2, 2), &verbs())
+    }
+
+    #[test]
+    fn fills_moves() {
+        assert_eq!(click(""), "C3");
+        assert_eq!(click("B2"), "B2:C3");
+        assert_eq!(click("A1-B2 "), "A1-B2:C3");
+        assert_eq!(click("B2:"), "B2:C3");
+        assert_eq!(click("B2: "), "B2:C3");
+        assert_eq!(click("A1- "), "A1-C3");
+        // Only the half typed cell is replaced
+        assert_eq!(click("B2:D"), "B2:C3");
+        assert_eq!(click("A1-B"), "A1-C3");
+        // A complete move starts a new one
+        assert_eq!(click("B2:D4"), "C3");
+        assert_eq!(click("nonsense"), "C3");
+    }
+
+    #[test]
+    fn fills_the_argument_being_typed() {
+        assert_eq!(click("build"), "build C3");
+        assert_eq!(click("build "), "build C3");
+        assert_eq!(click("BUILD B"), "BUILD C3");
+        assert_eq!(click("build B2"), "build C3");
+        assert_eq!(click("clear A1-"), "clear A1-C3");
+        assert_eq!(click("clear A1-B2 "), "clear A1-B2 C3");
+        assert_eq!(click("clear A1-B2 D"), "clear A1-B2 C3");
+        assert_eq!(click("clear A1-B2 D4"), "clear A1-B2 C3");
+    }
+
+    #[test]
+    fn keeps_arguments_that_arent_cells() {
+        assert_eq!(click("build B2 "), "build B2 ");
+        assert_eq!(click("build B2 wa"), "build B2 wa");
+    }
+
+    #[test]
+    fn complete_verbs_start_over() {
+        assert_eq!(click("help"), "C3");
+        assert_eq!(click("build B2 wall"), "C3");
+        assert_eq!(click("build B2 wall "), "C3");
+        assert_eq!(click("clear A1-B2 D4 "), "C3");
+    }
+}
@@ -0,0 +1,477 @@
+use bevy::{
+    ecs::system::SystemState,
+    prelude::*,
+    reflect::{DynamicStruct, DynamicTupleStruct, GetPath, ReflectRef, TypeInfo, TypeRegistration},
+};
+
+use super::{
+    Command, CommandExecuted, CommandInput, CommandIssued, CommandRejected, RejectReason, Side,
+};
+
+const HELP: &str = "get <Type>[.field]  show a resource or every component of that type
+set <Type>[.field] <value>  write it, a single field tuple struct can skip the .0
+spawn [Component]...  new entity with the components at their defaults
+despawn <entity>  eg. despawn 12v0
+list <Component>  entities that have it";
+
+/// While open, the command bar runs console lines instead of game commands
+#[derive(Resource, Debug, Default)]
+pub struct ConsoleState {
+    pub open: bool,
+}
+
+/// Characters the command bar accepts while the console is open
+pub(super) fn is_console_char(c: char) -> bool {
+    (c.is_ascii_graphic() && c != '`') || c == ' '
+}
+
+/// Backtick opens and closes the console
+pub(super) fn toggle_console(
+    keys: Res<Input<KeyCode>>,
+    mut console: ResMut<ConsoleState>,
+    mut command_input: ResMut<CommandInput>,
+) {
+    if keys.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+        // Suggestions and the placeholder depend on the mode
+        command_input.set_changed();
+    }
+}
+
+/// Runs the console lines issued this frame. Needs the whole world to reach any resource
+/// or component, so it runs at the end of the stage
+pub(super) fn run_console(world: &mut World, issued: &mut SystemState<EventReader<CommandIssued>>) {
+    let lines: Vec<(String, Side, String)> = issued
+        .get_mut(world)
+        .iter()
+        .filter_map(|ev| match &ev.command {
+            Command::Console(line) => Some((ev.input.clone(), ev.side, line.clone())),
+            _ => None,
+        })
+        .collect();
+
+    for (input, side, line) in lines {
+        match run_line(world, &line) {
+            Ok(output) => world.send_event(CommandExecuted {
+                input,
+                side,
+                command: Command::Console(line),
+                output: Some(output),
+            }),
+            Err(reason) => world.send_event(CommandRejected {
+                input,
+                side,
+                reason: RejectReason::Console(reason),
+            }),
+        }
+    }
+}
+
+fn run_line(world: &mut World, line: &str) -> Result<String, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["help"] => Ok(HELP.to_string()),
+        ["get", target] => get(world, target),
+        ["set", target, value @ ..] if !value.is_empty() => set(world, target, &value.join(" ")),
+        ["spawn", components @ ..] => spawn(world, components),
+        ["despawn", entity] => despawn(world, entity),
+        ["list", name] => list(world, name),
+        [verb, ..] => Err(format!("unknown console command '{}', type help", verb)),
+        [] => Err("nothing to run".to_string()),
+    }
+}
+
+/// A reflected type the console can reach
+enum Reflected {
+    Resource(ReflectResource),
+    Component(ReflectComponent),
+}
+
+/// Looks a type up by its short name, eg. `PanCam`, ignoring case
+fn lookup(world: &World, name: &str) -> Result<(Reflected, TypeRegistration), String> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let registration = registry
+        .iter()
+        .find(|registration| registration.short_name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("no reflected type called {}", name))?;
+
+    let reflected = if let Some(resource) = registration.data::<ReflectResource>() {
+        Reflected::Resource(resource.clone())
+    } else if let Some(component) = registration.data::<ReflectComponent>() {
+        Reflected::Component(component.clone())
+    } else {
+        return Err(format!(
+            "{} isn't reflected as a resource or component",
+            registration.short_name()
+        ));
+    };
+
+    Ok((reflected, registration.clone()))
+}
+
+/// `PanCam.min_scale` is the type `PanCam` and the path `min_scale`
+fn split_target(target: &str) -> (&str, &str) {
+    target.split_once('.').unwrap_or((target, ""))
+}
+
+fn with_component(world: &World, component: &ReflectComponent) -> Vec<Entity> {
+    world
+        .iter_entities()
+        .filter(|entity| component.reflect(world, *entity).is_some())
+        .collect()
+}
+
+fn field<'a>(value: &'a dyn Reflect, path: &str) -> Result<&'a dyn Reflect, String> {
+    match path {
+        "" => Ok(value),
+        path => value
+            .path(path)
+            .map_err(|error| format!("{}: {:?}", path, error)),
+    }
+}
+
+fn field_mut<'a>(value: &'a mut dyn Reflect, path: &str) -> Result<&'a mut dyn Reflect, String> {
+    // `set TimeScale 5` means its only field
+    let path = match value.reflect_ref() {
+        ReflectRef::TupleStruct(tuple) if path.is_empty() && tuple.field_len() == 1 => ".0",
+        _ => path,
+    };
+
+    match path {
+        "" => Ok(value),
+        path => value
+            .path_mut(path)
+            .map_err(|error| format!("{}: {:?}", path, error)),
+    }
+}
+
+fn get(world: &mut World, target: &str) -> Result<String, String> {
+    let (name, path) = split_target(target);
+    let (reflected, registration) = lookup(world, name)?;
+
+    match reflected {
+        Reflected::Resource(resource) => {
+            let value = resource
+                .reflect(world)
+                .ok_or_else(|| format!("{} isn't inserted", registration.short_name()))?;
+
+            Ok(format!("{:?}", field(value, path)?))
+        }
+        Reflected::Component(component) => {
+            let mut lines = vec![];
+
+            for entity in with_component(world, &component) {
+                if let Some(value) = component.reflect(world, entity) {
+                    lines.push(format!("{:?}: {:?}", entity, field(value, path)?));
+                }
+            }
+
+            match lines.is_empty() {
+                true => Err(format!("nothing has a {}", registration.short_name())),
+                false => Ok(lines.join("\n")),
+            }
+        }
+    }
+}
+
+fn set(world: &mut World, target: &str, text: &str) -> Result<String, String> {
+    let (name, path) = split_target(target);
+    let (reflected, registration) = lookup(world, name)?;
+
+    match reflected {
+        Reflected::Resource(resource) => {
+            let mut value = resource
+                .reflect_mut(world)
+                .ok_or_else(|| format!("{} isn't inserted", registration.short_name()))?;
+
+            set_value(field_mut(&mut *value, path)?, text)?;
+            Ok(format!("{} = {}", target, text))
+        }
+        Reflected::Component(component) => {
+            let entities = with_component(world, &component);
+
+            if entities.is_empty() {
+                return Err(format!("nothing has a {}", registration.short_name()));
+            }
+
+            for entity in entities.iter() {
+                if let Some(mut value) = component.reflect_mut(world, *entity) {
+                    set_value(field_mut(&mut *value, path)?, text)?;
+                }
+            }
+
+            Ok(format!(
+                "{} = {} on {} entities",
+                target,
+                text,
+                entities.len()
+            ))
+        }
+    }
+}
+
+/// Parses `text` as whatever type `target` is and writes it
+fn set_value(target: &mut dyn Reflect, text: &str) -> Result<(), String> {
+    macro_rules! parse_into {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = target.downcast_mut::<$ty>() {
+                    *value = text
+                        .parse()
+                        .map_err(|_| format!("expected a {}", stringify!($ty)))?;
+                    return Ok(());
+                }
+            )*
+        };
+    }
+
+    parse_into!(f32, f64, i32, i64, u32, u64, usize, bool, String);
+
+    if let Some(value) = target.downcast_mut::<Option<f32>>() {
+        *value = match text {
+            "none" => None,
+            text => Some(text.parse().map_err(|_| "expected a f32 or none")?),
+        };
+        return Ok(());
+    }
+
+    Err(format!("can't set a {}", target.type_name()))
+}
+
+fn spawn(world: &mut World, names: &[&str]) -> Result<String, String> {
+    let mut components = vec![];
+
+    // Check everything first so a typo doesn't leave a half built entity behind
+    for name in names {
+        let (reflected, registration) = lookup(world, name)?;
+
+        let component = match reflected {
+            Reflected::Component(component) => component,
+            Reflected::Resource(_) => return Err(format!("{} is a resource", name)),
+        };
+
+        // Applying an empty value keeps the component's defaults
+        let empty: Box<dyn Reflect> = match registration.type_info() {
+            TypeInfo::Struct(_) => Box::new(DynamicStruct::default()),
+            TypeInfo::TupleStruct(_) => Box::new(DynamicTupleStruct::default()),
+            _ => return Err(format!("can't spawn a {}", registration.short_name())),
+        };
+
+        components.push((component, empty));
+    }
+
+    let entity = world.spawn_empty().id();
+
+    for (component, empty) in components {
+        component.insert(world, entity, &*empty);
+    }
+
+    Ok(format!("spawned {:?}", entity))
+}
+
+/// Entities are written the way they are printed, eg. `12v0`. The generation can be
+/// left off when it is 0
+fn parse_entity(text: &str) -> Option<Entity> {
+    let (index, generation) = text.split_once('v').unwrap_or((text, "0"));
+    let bits = (generation.parse::<u64>().ok()? << 32) | index.parse::<u32>().ok()? as u64;
+
+    Some(Entity::from_bits(bits))
+}
+
+fn despawn(world: &mut World, text: &str) -> Result<String, String> {
+    let entity = parse_entity(text).ok_or_else(|| format!("{} isn't an entity, eg. 12v0", text))?;
+
+    match world.get_entity_mut(entity) {
+        Some(entity_mut) => {
+            entity_mut.despawn_recursive();
+            Ok(format!("despawned {:?}", entity))
+        }
+        None => Err(format!("{:?} doesn't exist", entity)),
+    }
+}
+
+fn list(world: &mut World, name: &str) -> Result<String, String> {
+    let (reflected, registration) = lookup(world, name)?;
+
+    let component = match reflected {
+        Reflected::Component(component) => component,
+        Reflected::Resource(_) => return Err(format!("{} is a resource, use get", name)),
+    };
+
+    let lines: Vec<String> = with_component(world, &component)
+        .into_iter()
+        .map(|entity| match world.get::<Name>(entity) {
+            Some(entity_name) => format!("{:?} {}", entity, entity_name),
+            None => format!("{:?}", entity),
+        })
+        .collect();
+
+    match lines.is_empty() {
+        true => Err(format!("nothing has a {}", registration.short_name())),
+        false => Ok(lines.join("\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_defender::game::GameStats;
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct TimeScale(f32);
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Speed {
+        value: f32,
+        boosted: bool,
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<GameStats>();
+            registry.register::<TimeScale>();
+            registry.register::<Speed>();
+            registry.register::<Name>();
+        }
+
+        world.insert_resource(registry);
+        world.insert_resource(GameStats {
+            gold: 5,
+            ..default()
+        });
+        world.insert_resource(TimeScale(1.0));
+        world
+    }
+
+    #[test]
+    fn splits_type_and_path() {
+        assert_eq!(split_target("PanCam.min_scale"), ("PanCam", "min_scale"));
+        assert_eq!(split_target("PanCam"), ("PanCam", ""));
+        assert_eq!(
+            split_target("Transform.translation.x"),
+            ("Transform", "translation.x")
+        );
+    }
+
+    #[test]
+    fn sets_and_gets_a_resource_field() {
+        let mut world = world();
+
+        assert_eq!(
+            run_line(&mut world, "get GameStats.gold"),
+            Ok("5".to_string())
+        );
+        assert_eq!(
+            run_line(&mut world, "set gamestats.gold 42"),
+            Ok("gamestats.gold = 42".to_string())
+        );
+        assert_eq!(world.resource::<GameStats>().gold, 42);
+        assert_eq!(
+            run_line(&mut world, "get GAMESTATS.gold"),
+            Ok("42".to_string())
+        );
+    }
+
+    #[test]
+    fn sets_a_single_field_tuple_struct() {
+        let mut world = world();
+
+        assert!(run_line(&mut world, "set TimeScale 2.5").is_ok());
+        assert_eq!(world.resource::<TimeScale>().0, 2.5);
+        assert_eq!(
+            run_line(&mut world, "get TimeScale.0"),
+            Ok("2.5".to_string())
+        );
+    }
+
+    #[test]
+    fn sets_every_component() {
+        let mut world = world();
+        world.spawn(Speed::default());
+        world.spawn(Speed::default());
+
+        assert_eq!(
+            run_line(&mut world, "set Speed.boosted true"),
+            Ok("Speed.boosted = true on 2 entities".to_string())
+        );
+        assert!(world
+            .query::<&Speed>()
+            .iter(&world)
+            .all(|speed| speed.boosted));
+    }
+
+    #[test]
+    fn bad_paths_are_errors() {
+        let mut world = world();
+
+        let error = run_line(&mut world, "get GameStats.silver").unwrap_err();
+        assert!(error.starts_with("silver: "), "{}", error);
+        let error = run_line(&mut world, "set GameStats.silver 1").unwrap_err();
+        assert!(error.starts_with("silver: "), "{}", error);
+
+        assert_eq!(
+            run_line(&mut world, "get Nothing.gold"),
+            Err("no reflected type called Nothing".to_string())
+        );
+        assert_eq!(
+            run_line(&mut world, "get Speed"),
+            Err("nothing has a Speed".to_string())
+        );
+    }
+
+    #[test]
+    fn type_mismatches_are_errors() {
+        let mut world = world();
+
+        assert_eq!(
+            run_line(&mut world, "set GameStats.gold lots"),
+            Err("expected a u32".to_string())
+        );
+        assert_eq!(
+            run_line(&mut world, "set GameStats.gold -1"),
+            Err("expected a u32".to_string())
+        );
+        assert_eq!(world.resource::<GameStats>().gold, 5);
+
+        let error = run_line(&mut world, "set GameStats 1").unwrap_err();
+        assert!(error.starts_with("can't set a "), "{}", error);
+    }
+
+    #[test]
+    fn spawns_lists_and_despawns() {
+        let mut world = world();
+
+        assert!(run_line(&mut world, "spawn Speed Nope").is_err());
+        assert_eq!(world.entities().len(), 0);
+
+        let spawned = run_line(&mut world, "spawn Speed").unwrap();
+        let entity = world.query_filtered::<Entity, With<Speed>>().single(&world);
+        assert_eq!(spawned, format!("spawned {:?}", entity));
+        assert_eq!(
+            run_line(&mut world, "list Speed"),
+            Ok(format!("{:?}", entity))
+        );
+
+        assert_eq!(
+            run_line(&mut world, &format!("despawn {}", entity.index())),
+            Ok(format!("despawned {:?}", entity))
+        );
+        assert!(world.get_entity(entity).is_none());
+        assert!(run_line(&mut world, "despawn 12x").is_err());
+    }
+
+    #[test]
+    fn parses_entities() {
+        assert_eq!(parse_entity("12v0"), Some(Entity::from_raw(12)));
+        assert_eq!(parse_entity("12"), Some(Entity::from_raw(12)));
+        assert_eq!(parse_entity("3v2"), Some(Entity::from_bits((2 << 32) | 3)));
+        assert_eq!(parse_entity("v2"), None);
+        assert_eq!(parse_entity("entity"), None);
+    }
+}
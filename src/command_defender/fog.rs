@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use super::{
+    board::{Board, BoardPosition, Unit},
+    game::{self, Enemy},
+    parser::GridCoord,
+    Command, LocalSide, RejectReason, Side,
+};
+
+/// Cells, in any direction, a unit or enemy can see
+const SIGHT_RADIUS: u32 = 3;
+
+const FOG_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+
+/// Cells each side can see from its pieces. Commands can only target cells their side
+/// sees, both sides are kept so the other player's commands are checked the same way
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct FogOfWar {
+    /// Off, everything is seen
+    pub enabled: bool,
+    /// Spectating, the whole board is drawn. Commands still need vision
+    pub reveal_all: bool,
+    #[reflect(ignore)]
+    defender: HashSet<GridCoord>,
+    #[reflect(ignore)]
+    attacker: HashSet<GridCoord>,
+}
+
+impl FogOfWar {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..default()
+        }
+    }
+
+    pub fn is_visible(&self, side: Side, coord: GridCoord) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        match side {
+            Side::Defender => self.defender.contains(&coord),
+            Side::Attacker => self.attacker.contains(&coord),
+        }
+    }
+
+    /// Rejects a command aimed at a cell `side` can't see. Cells off the board are left
+    /// for the command to reject
+    pub fn check(&self, board: &Board, side: Side, command: &Command) -> Result<(), RejectReason> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        match command
+            .targets(board)
+            .into_iter()
+            .find(|coord| !self.is_visible(side, *coord))
+        {
+            Some(coord) => Err(RejectReason::NoVision(coord)),
+            None => Ok(()),
+        }
+    }
+
+    /// Drawn for the player here
+    fn is_shown(&self, side: Side, coord: GridCoord) -> bool {
+        self.reveal_all || self.is_visible(side, coord)
+    }
+}
+
+/// Darkens a cell the local side can't see
+#[derive(Component)]
+pub(super) struct FogCell(GridCoord);
+
+fn sight(board: &Board, pieces: impl Iterator<Item = GridCoord>) -> HashSet<GridCoord> {
+    let pieces: Vec<GridCoord> = pieces.collect();
+
+    board
+        .cells()
+        .filter(|cell| {
+            pieces
+                .iter()
+                .any(|piece| game::distance(*piece, *cell) <= SIGHT_RADIUS)
+        })
+        .collect()
+}
+
+/// Only touches `FogOfWar` when the sight changes so drawing can wait for it
+pub(super) fn update_fog(
+    board: Res<Board>,
+    mut fog: ResMut<FogOfWar>,
+    units: Query<&BoardPosition, With<Unit>>,
+    enemies: Query<&BoardPosition, With<Enemy>>,
+) {
+    if !fog.enabled {
+        return;
+    }
+
+    let defender = sight(&board, units.iter().map(|position| position.0));
+    let attacker = sight(&board, enemies.iter().map(|position| position.0));
+
+    if fog.defender != defender {
+        fog.defender = defender;
+    }
+    if fog.attacker != attacker {
+        fog.attacker = attacker;
+    }
+}
+
+/// F2 toggles spectating
+pub(super) fn toggle_spectator(keys: Res<Input<KeyCode>>, mut fog: ResMut<FogOfWar>) {
+    if keys.just_pressed(KeyCode::F2) {
+        fog.reveal_all = !fog.reveal_all;
+    }
+}
+
+pub(super) fn spawn_fog(mut commands: Commands, board: Res<Board>) {
+    for coord in board.cells() {
+        commands.spawn((
+            FogCell(coord),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: FOG_COLOR,
+                    custom_size: Some(Vec2::splat(board.cell_size)),
+                    ..default()
+                },
+                // Over the pieces
+                transform: Transform::from_translation(board.cell_center(coord).extend(3.0)),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Darkens unseen cells and hides the other side's pieces in them
+pub(super) fn render_fog(
+    fog: Res<FogOfWar>,
+    local: Res<LocalSide>,
+    mut cells: Query<(&FogCell, &mut Visibility)>,
+    mut pieces: Query<(&BoardPosition, Option<&Enemy>, &mut Visibility), Without<FogCell>>,
+) {
+    if fog.is_changed() {
+        for (cell, mut visibility) in cells.iter_mut() {
+            visibility.is_visible = !fog.is_shown(local.0, cell.0);
+        }
+    }
+
+    // Pieces move without the fog changing, so these are checked every frame
+    for (position, enemy, mut visibility) in pieces.iter_mut() {
+        let owner = match enemy {
+            Some(_) => Side::Attacker,
+            None => Side::Defender,
+        };
+        let is_visible = owner == local.0 || fog.is_shown(local.0, position.0);
+
+        if visibility.is_visible != is_visible {
+            visibility.is_visible = is_visible;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_defender::{
+        verbs::{ArgValue, VerbArgs},
+        ArgKind, GridRange, VerbRegistry, VerbSpec,
+    };
+
+    fn coord(s: &str) -> GridCoord {
+        s.parse().unwrap()
+    }
+
+    fn command(input: &str) -> Command {
+        let mut verbs = VerbRegistry::default();
+        verbs.insert(VerbSpec::new("scout", "").arg("cells", ArgKind::Range));
+        Command::parse(input, &verbs).unwrap()
+    }
+
+    /// The defender sees the first two columns of a 6x4 board
+    fn fog() -> (Board, FogOfWar) {
+        let board = Board::new(6, 4, 10.0);
+        let mut fog = FogOfWar::new(true);
+        fog.defender = board.cells().filter(|cell| cell.column < 2).collect();
+        (board, fog)
+    }
+
+    #[test]
+    fn moves_target_every_destination() {
+        let board = Board::new(6, 4, 10.0);
+
+        assert_eq!(command("A1:B2").targets(&board), [coord("B2")]);
+        assert_eq!(
+            command("A1-B2:C3").targets(&board),
+            [coord("C3"), coord("D3"), coord("C4"), coord("D4")]
+        );
+        // Typed bottom up, `to` is where the first corner lands
+        assert_eq!(
+            command("A2-A1:C3").targets(&board),
+            [coord("C2"), coord("C3")]
+        );
+        // Anything off the board is for the move to reject
+        assert_eq!(
+            command("A1-A3:F3").targets(&board),
+            [coord("F3"), coord("F4")]
+        );
+    }
+
+    #[test]
+    fn ranges_are_clamped_to_the_board() {
+        let board = Board::new(6, 4, 10.0);
+        let huge = Command::Verb(VerbArgs {
+            verb: "scout",
+            values: vec![Some(ArgValue::Range(GridRange {
+                start: GridCoord::new(0, 0),
+                end: GridCoord::new(u32::MAX, u32::MAX),
+            }))],
+        });
+
+        assert_eq!(huge.targets(&board), board.cells().collect::<Vec<_>>());
+        assert_eq!(command("scout E3-Z99").targets(&board).len(), 4);
+        assert!(command("scout G1-H2").targets(&board).is_empty());
+        assert!(command("G1-Z99:A1").targets(&board).is_empty());
+    }
+
+    #[test]
+    fn range_moves_into_fog_are_rejected() {
+        let (board, fog) = fog();
+
+        assert_eq!(
+            fog.check(&board, Side::Defender, &command("A1-A2:B1")),
+            Ok(())
+        );
+        // The first cell lands in sight, the second doesn't
+        assert_eq!(
+            fog.check(&board, Side::Defender, &command("A1-B1:B1")),
+            Err(RejectReason::NoVision(coord("C1")))
+        );
+        assert_eq!(
+            fog.check(&board, Side::Defender, &command("scout A1-C1")),
+            Err(RejectReason::NoVision(coord("C1")))
+        );
+        // Off the board is left for the move itself
+        assert_eq!(fog.check(&board, Side::Defender, &command("A1:A9")), Ok(()));
+    }
+
+    #[test]
+    fn nothing_is_checked_without_fog() {
+        let (board, mut fog) = fog();
+        fog.enabled = false;
+
+        assert_eq!(
+            fog.check(&board, Side::Defender, &command("A1-B1:E1")),
+            Ok(())
+        );
+        assert_eq!(
+            fog.check(&board, Side::Attacker, &command("scout A1-F4")),
+            Ok(())
+        );
+    }
+}
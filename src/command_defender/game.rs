@@ -0,0 +1,606 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::{
+    board::{self, Board, BoardPosition, StartingUnits, Unit},
+    parser::GridCoord,
+    undo::{BoardAction, UndoHistory},
+    verbs::{FromVerbArgs, VerbArgs, VerbCall},
+    CommandExecuted, CommandRejected,
+};
+
+pub const UNIT_HEALTH: u32 = 3;
+const WALL_HEALTH: u32 = 6;
+/// Cells, in any direction, a unit can shoot
+pub(super) const ATTACK_RANGE: u32 = 2;
+/// Ticks a unit waits after shooting
+const RELOAD_TICKS: u32 = 2;
+const UNIT_COST: u32 = 5;
+const WALL_COST: u32 = 2;
+/// Building is only allowed in the columns closest to the base
+const BUILD_COLUMNS: u32 = 4;
+const KILL_SCORE: u32 = 10;
+/// Ticks between enemies of the same wave
+const SPAWN_TICKS: u32 = 2;
+/// Ticks of quiet between waves
+const WAVE_GAP_TICKS: u32 = 6;
+
+const ENEMY_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+const WALL_COLOR: Color = Color::rgb(0.55, 0.55, 0.6);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    #[default]
+    Casual,
+    /// Play against someone else, undo is turned off
+    Competitive,
+}
+
+/// Label for the stage that runs the game on a fixed timestep
+#[derive(StageLabel)]
+pub struct GameTickStage;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Returns `true` once it is dead
+    pub fn damage(&mut self, amount: u32) -> bool {
+        self.current = self.current.saturating_sub(amount);
+        self.current == 0
+    }
+}
+
+/// Advances from the right edge of the board towards the base on the left
+#[derive(Component, Debug, Default)]
+pub struct Enemy;
+
+/// Blocks enemies, can't move or shoot
+#[derive(Component, Debug, Default)]
+pub struct Wall;
+
+/// Ticks until a unit can shoot again
+#[derive(Component, Debug, Default)]
+pub struct Reload(pub u32);
+
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct GameStats {
+    pub base_health: u32,
+    pub max_base_health: u32,
+    pub score: u32,
+    /// Spent on `build`, earned by killing enemies
+    pub gold: u32,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct Waves {
+    /// Wave being spawned, starts at 0 before the first
+    pub current: u32,
+    pub total: u32,
+    /// Enemies of the current wave still to spawn
+    pub to_spawn: u32,
+    /// Ticks until the next spawn
+    pub countdown: u32,
+}
+
+impl Waves {
+    pub fn new(total: u32) -> Self {
+        Self {
+            current: 0,
+            total,
+            to_spawn: 0,
+            countdown: WAVE_GAP_TICKS,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.total && self.to_spawn == 0
+    }
+
+    fn wave_size(wave: u32) -> u32 {
+        3 + wave * 2
+    }
+
+    fn enemy_health(wave: u32) -> u32 {
+        1 + wave / 2
+    }
+}
+
+/// Picks where enemies spawn. Seeded the same on both ends of a networked game so they
+/// spawn the same waves
+#[derive(Resource)]
+pub(super) struct WaveRng(StdRng);
+
+impl WaveRng {
+    pub fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Self(StdRng::seed_from_u64(seed)),
+            None => Self(StdRng::from_entropy()),
+        }
+    }
+}
+
+/// What a new game starts with, kept for `restart`
+#[derive(Resource, Clone)]
+pub(super) struct GameSettings {
+    pub waves: u32,
+    pub base_health: u32,
+    pub starting_gold: u32,
+}
+
+impl GameSettings {
+    pub fn stats(&self) -> GameStats {
+        GameStats {
+            base_health: self.base_health,
+            max_base_health: self.base_health,
+            score: 0,
+            gold: self.starting_gold,
+        }
+    }
+}
+
+pub(super) fn is_playing(state: &State<GameState>) -> bool {
+    *state.current() == GameState::Playing
+}
+
+pub(super) fn reload_units(state: Res<State<GameState>>, mut query: Query<&mut Reload>) {
+    if !is_playing(&state) {
+        return;
+    }
+
+    for mut reload in query.iter_mut() {
+        reload.0 = reload.0.saturating_sub(1);
+    }
+}
+
+/// Every enemy takes a step left, attacks whatever blocks it, or hits the base
+/// when it walks off the board
+pub(super) fn advance_enemies(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut board: ResMut<Board>,
+    mut stats: ResMut<GameStats>,
+    mut enemies: Query<(Entity, &mut BoardPosition), With<Enemy>>,
+    mut blockers: Query<&mut Health, Without<Enemy>>,
+) {
+    if !is_playing(&state) {
+        return;
+    }
+
+    // Front of the line first so the ones behind can follow into the cells it leaves
+    let mut order: Vec<(Entity, GridCoord)> = enemies.iter().map(|(e, p)| (e, p.0)).collect();
+    order.sort_by_key(|(_, coord)| coord.column);
+
+    for (entity, coord) in order {
+        if coord.column == 0 {
+            stats.base_health = stats.base_health.saturating_sub(1);
+            board.vacate(entity);
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let ahead = GridCoord::new(coord.column - 1, coord.row);
+
+        match board.occupant(ahead) {
+            None => {
+                board.place(entity, ahead);
+
+                if let Ok((_, mut position)) = enemies.get_mut(entity) {
+                    position.0 = ahead;
+                }
+            }
+            Some(blocker) => {
+                // Other enemies just wait their turn
+                if let Ok(mut health) = blockers.get_mut(blocker) {
+                    if health.damage(1) {
+                        board.vacate(blocker);
+                        commands.entity(blocker).despawn();
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn spawn_waves(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut board: ResMut<Board>,
+    mut waves: ResMut<Waves>,
+    mut rng: ResMut<WaveRng>,
+) {
+    if !is_playing(&state) || waves.is_finished() {
+        return;
+    }
+
+    if waves.countdown > 0 {
+        waves.countdown -= 1;
+        return;
+    }
+
+    if waves.to_spawn == 0 {
+        waves.current += 1;
+        waves.to_spawn = Waves::wave_size(waves.current);
+    }
+
+    let column = board.columns.saturating_sub(1);
+    let free: Vec<GridCoord> = (0..board.rows)
+        .map(|row| GridCoord::new(column, row))
+        .filter(|coord| board.occupant(*coord).is_none())
+        .collect();
+
+    // The spawn column is full, try again next tick
+    let coord = match free.choose(&mut rng.0) {
+        Some(coord) => *coord,
+        None => return,
+    };
+
+    board::spawn_piece(&mut commands, &mut board, coord, ENEMY_COLOR, 0.5).insert((
+        Enemy,
+        Health::new(Waves::enemy_health(waves.current)),
+        Name::new("Enemy"),
+    ));
+
+    waves.to_spawn -= 1;
+    waves.countdown = match waves.to_spawn {
+        0 => WAVE_GAP_TICKS,
+        _ => SPAWN_TICKS,
+    };
+}
+
+pub(super) fn check_outcome(
+    mut state: ResMut<State<GameState>>,
+    stats: Res<GameStats>,
+    waves: Res<Waves>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    if !is_playing(&state) {
+        return;
+    }
+
+    let outcome = if stats.base_health == 0 {
+        GameState::Lost
+    } else if waves.is_finished() && enemies.is_empty() {
+        GameState::Won
+    } else {
+        return;
+    };
+
+    if let Err(error) = state.set(outcome) {
+        warn!("Couldn't end the game: {:?}", error);
+    }
+}
+
+/// Fades pieces out as they take damage
+pub(super) fn show_damage(mut query: Query<(&Health, &mut Sprite), Changed<Health>>) {
+    for (health, mut sprite) in query.iter_mut() {
+        let alpha = 0.35 + 0.65 * health.current as f32 / health.max.max(1) as f32;
+        sprite.color.set_a(alpha);
+    }
+}
+
+/// `attack <target>`
+pub(super) struct Attack {
+    target: GridCoord,
+}
+
+impl FromVerbArgs for Attack {
+    fn from_args(args: &VerbArgs) -> Option<Self> {
+        Some(Attack {
+            target: args.coord(0)?,
+        })
+    }
+}
+
+/// Every loaded unit in range of the target shoots it once
+#[allow(clippy::too_many_arguments)]
+pub(super) fn attack(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut board: ResMut<Board>,
+    mut stats: ResMut<GameStats>,
+    mut ev_attack: EventReader<VerbCall<Attack>>,
+    mut units: Query<(&BoardPosition, &mut Reload), With<Unit>>,
+    mut enemies: Query<&mut Health, With<Enemy>>,
+    mut ev_executed: EventWriter<CommandExecuted>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    for ev in ev_attack.iter() {
+        if let Some(rejected) = ev.defender_only() {
+            ev_rejected.send(rejected);
+            continue;
+        }
+
+        if !is_playing(&state) {
+            ev_rejected.send(ev.rejected("the game is over, type restart"));
+            continue;
+        }
+
+        let target = ev.args.target;
+        let enemy = match board.occupant(target).filter(|e| enemies.contains(*e)) {
+            Some(enemy) => enemy,
+            None => {
+                ev_rejected.send(ev.rejected(format!("no enemy at {}", target)));
+                continue;
+            }
+        };
+
+        let mut in_range: Vec<Mut<Reload>> = units
+            .iter_mut()
+            .filter(|(position, _)| distance(position.0, target) <= ATTACK_RANGE)
+            .map(|(_, reload)| reload)
+            .collect();
+
+        if in_range.is_empty() {
+            ev_rejected.send(ev.rejected(format!("no units in range of {}", target)));
+            continue;
+        }
+
+        let mut shots = 0;
+
+        for reload in in_range.iter_mut().filter(|reload| reload.0 == 0) {
+            reload.0 = RELOAD_TICKS;
+            shots += 1;
+        }
+
+        if shots == 0 {
+            ev_rejected.send(ev.rejected("units in range are reloading"));
+            continue;
+        }
+
+        let killed = enemies
+            .get_mut(enemy)
+            .is_ok_and(|mut health| health.damage(shots));
+
+        if killed {
+            board.vacate(enemy);
+            commands.entity(enemy).despawn();
+            stats.score += KILL_SCORE;
+            stats.gold += 1;
+        }
+
+        ev_executed.send(ev.executed());
+    }
+}
+
+/// Cells between two coordinates, counting diagonals as one step
+pub(super) fn distance(a: GridCoord, b: GridCoord) -> u32 {
+    a.column.abs_diff(b.column).max(a.row.abs_diff(b.row))
+}
+
+/// `build <cell> <unit|wall>`
+pub(super) struct Build {
+    cell: GridCoord,
+    kind: &'static str,
+}
+
+impl FromVerbArgs for Build {
+    fn from_args(args: &VerbArgs) -> Option<Self> {
+        Some(Build {
+            cell: args.coord(0)?,
+            kind: args.word(1)?,
+        })
+    }
+}
+
+pub const BUILD_KINDS: &[&str] = &["unit", "wall"];
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn build(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut board: ResMut<Board>,
+    mut stats: ResMut<GameStats>,
+    mut undo: ResMut<UndoHistory>,
+    mut ev_build: EventReader<VerbCall<Build>>,
+    mut ev_executed: EventWriter<CommandExecuted>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    for ev in ev_build.iter() {
+        if let Some(rejected) = ev.defender_only() {
+            ev_rejected.send(rejected);
+            continue;
+        }
+
+        let (cell, kind) = (ev.args.cell, ev.args.kind);
+
+        let cost = build_cost(kind);
+
+        let reason = if !is_playing(&state) {
+            Some("the game is over, type restart".to_string())
+        } else if !board.contains(cell) {
+            Some(format!("{} is off the board", cell))
+        } else if cell.column >= BUILD_COLUMNS {
+            Some(format!(
+                "can only build in columns A to {}",
+                GridCoord::column_label(BUILD_COLUMNS - 1)
+            ))
+        } else if board.occupant(cell).is_some() {
+            Some(format!("{} is already occupied", cell))
+        } else if stats.gold < cost {
+            Some(format!(
+                "a {} costs {} gold, you have {}",
+                kind, cost, stats.gold
+            ))
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            ev_rejected.send(ev.rejected(reason));
+            continue;
+        }
+
+        stats.gold -= cost;
+
+        if let Some(entity) = spawn_building(&mut commands, &mut board, cell, kind) {
+            undo.record(BoardAction::Build {
+                entity,
+                cell,
+                kind,
+                cost,
+            });
+        }
+
+        ev_executed.send(ev.executed());
+    }
+}
+
+pub(super) fn build_cost(kind: &str) -> u32 {
+    match kind {
+        "wall" => WALL_COST,
+        _ => UNIT_COST,
+    }
+}
+
+/// Spawns one of `BUILD_KINDS`, `None` when the cell is off the board
+pub(super) fn spawn_building(
+    commands: &mut Commands,
+    board: &mut Board,
+    cell: GridCoord,
+    kind: &str,
+) -> Option<Entity> {
+    match kind {
+        "wall" if board.contains(cell) => {
+            let wall = board::spawn_piece(commands, board, cell, WALL_COLOR, 0.9)
+                .insert((
+                    Wall,
+                    Health::new(WALL_HEALTH),
+                    Name::new(format!("Wall {}", cell)),
+                ))
+                .id();
+
+            Some(wall)
+        }
+        "wall" => None,
+        _ => board::spawn_unit(commands, board, cell),
+    }
+}
+
+/// `restart`
+pub(super) struct Restart;
+
+impl FromVerbArgs for Restart {
+    fn from_args(_: &VerbArgs) -> Option<Self> {
+        Some(Restart)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn restart(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    mut board: ResMut<Board>,
+    settings: Res<GameSettings>,
+    starting_units: Res<StartingUnits>,
+    mut undo: ResMut<UndoHistory>,
+    mut ev_restart: EventReader<VerbCall<Restart>>,
+    pieces: Query<Entity, With<BoardPosition>>,
+    mut ev_executed: EventWriter<CommandExecuted>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    let mut last = None;
+
+    for ev in ev_restart.iter() {
+        match ev.defender_only() {
+            Some(rejected) => ev_rejected.send(rejected),
+            None => last = Some(ev),
+        }
+    }
+
+    let ev = match last {
+        Some(ev) => ev,
+        None => return,
+    };
+
+    for entity in pieces.iter() {
+        board.vacate(entity);
+        commands.entity(entity).despawn();
+    }
+
+    for coord in starting_units.0.iter() {
+        board::spawn_unit(&mut commands, &mut board, *coord);
+    }
+
+    commands.insert_resource(settings.stats());
+    commands.insert_resource(Waves::new(settings.waves));
+    undo.clear();
+
+    if !is_playing(&state) {
+        if let Err(error) = state.set(GameState::Playing) {
+            warn!("Couldn't restart the game: {:?}", error);
+        }
+    }
+
+    ev_executed.send(ev.executed());
+}
+
+#[derive(Component)]
+pub(super) struct Hud;
+
+pub(super) fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Hud,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(12.0),
+                bottom: Val::Px(10.0),
+                ..default()
+            },
+            ..default()
+        }),
+    ));
+}
+
+pub(super) fn update_hud(
+    state: Res<State<GameState>>,
+    stats: Res<GameStats>,
+    waves: Res<Waves>,
+    mut query: Query<&mut Text, With<Hud>>,
+) {
+    if !state.is_changed() && !stats.is_changed() && !waves.is_changed() {
+        return;
+    }
+
+    let mut value = format!(
+        "Wave {}/{}   Base {}/{}   Score {}   Gold {}",
+        waves.current,
+        waves.total,
+        stats.base_health,
+        stats.max_base_health,
+        stats.score,
+        stats.gold
+    );
+
+    match state.current() {
+        GameState::Playing => {}
+        GameState::Won => value.push_str("   You held the line! Type restart to play again"),
+        GameState::Lost => value.push_str("   The base fell. Type restart to try again"),
+    }
+
+    query.single_mut().sections[0].value = value;
+}
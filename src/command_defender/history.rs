@@ -0,0 +1,505 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use bevy::{input::mouse::MouseWheel, prelude::*};
+
+use super::{
+    input::CommandFocus, CommandExecuted, CommandInput, CommandRejected, LocalCommand, LocalSide,
+    Side,
+};
+
+/// Lines of the log shown at once
+const LOG_LINES: usize = 12;
+
+const EXECUTED_COLOR: Color = Color::rgb(0.5, 0.85, 0.5);
+const REJECTED_COLOR: Color = Color::rgb(0.9, 0.45, 0.4);
+const OUTPUT_COLOR: Color = Color::GRAY;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Executed,
+    /// Why it was rejected
+    Rejected(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub input: String,
+    pub outcome: Outcome,
+    /// Shown under the command in the log, not saved
+    pub output: Option<String>,
+    /// The other side's commands are logged too, but not saved or recalled
+    pub by: Option<Side>,
+}
+
+impl HistoryEntry {
+    /// One line per entry, tab separated: `ok<TAB>B2:C3` or `err<TAB>B2:Z9<TAB>reason`.
+    /// Reasons can span lines, so tabs, newlines and backslashes are escaped
+    fn to_line(&self) -> String {
+        match &self.outcome {
+            Outcome::Executed => format!("ok\t{}", escape(&self.input)),
+            Outcome::Rejected(reason) => {
+                format!("err\t{}\t{}", escape(&self.input), escape(reason))
+            }
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut parts = line.splitn(3, '\t');
+
+        let outcome = match (parts.next()?, parts.next()?, parts.next()) {
+            ("ok", input, None) => (input, Outcome::Executed),
+            ("err", input, Some(reason)) => (input, Outcome::Rejected(unescape(reason))),
+            _ => return None,
+        };
+
+        Some(Self {
+            input: unescape(outcome.0),
+            outcome: outcome.1,
+            output: None,
+            by: None,
+        })
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            // `\\` and anything unknown is kept as written
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+/// Every command that was submitted, oldest first, and where Up/Down recall is at
+#[derive(Resource, Debug, Default)]
+pub struct CommandHistory {
+    pub entries: Vec<HistoryEntry>,
+    /// Only keeps this many entries, in memory and on disk
+    pub limit: usize,
+    /// File the history is saved to, `None` keeps it in memory only
+    pub path: Option<PathBuf>,
+    /// Entry currently recalled into the input
+    recalled: Option<usize>,
+    /// What was typed before recalling, restored when going past the newest entry
+    draft: String,
+    /// Entries scrolled up from the bottom of the log
+    scroll: usize,
+}
+
+impl CommandHistory {
+    pub fn new(limit: usize, path: Option<PathBuf>) -> Self {
+        Self {
+            limit,
+            path,
+            ..default()
+        }
+    }
+
+    /// `<data dir>/<crate name>/command_history.txt`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("command_history.txt"))
+    }
+
+    pub fn push(&mut self, entry: HistoryEntry) {
+        if entry.by.is_none() {
+            self.save(&entry);
+        }
+
+        self.entries.push(entry);
+
+        if self.entries.len() > self.limit {
+            let excess = self.entries.len() - self.limit;
+            self.entries.drain(..excess);
+        }
+
+        self.scroll = 0;
+    }
+
+    fn load(&mut self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        // Missing is fine, it just hasn't been written yet
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return,
+        };
+
+        let mut entries: Vec<HistoryEntry> = contents
+            .lines()
+            .filter_map(HistoryEntry::from_line)
+            .collect();
+
+        // The file is only ever appended to, trim it back down once it grows too long
+        if entries.len() > self.limit {
+            entries.drain(..entries.len() - self.limit);
+
+            let lines: String = entries.iter().map(|e| e.to_line() + "\n").collect();
+
+            if let Err(error) = fs::write(path, lines) {
+                warn!("Couldn't trim command history {:?}: {}", path, error);
+            }
+        }
+
+        self.entries = entries;
+    }
+
+    fn save(&self, entry: &HistoryEntry) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(path))
+            .and_then(|mut file| writeln!(file, "{}", entry.to_line()));
+
+        if let Err(error) = result {
+            warn!("Couldn't save command history to {:?}: {}", path, error);
+        }
+    }
+}
+
+/// Panel behind the log, hovering it lets the mouse wheel scroll
+#[derive(Component)]
+pub(super) struct HistoryPanel;
+
+#[derive(Component)]
+pub(super) struct HistoryLog;
+
+pub(super) fn load_history(mut history: ResMut<CommandHistory>) {
+    history.load();
+}
+
+pub(super) fn spawn_history_log(mut commands: Commands) {
+    commands
+        .spawn((
+            HistoryPanel,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(12.0),
+                        top: Val::Px(62.0),
+                        ..default()
+                    },
+                    max_size: Size::new(Val::Px(320.0), Val::Undefined),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            Interaction::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((HistoryLog, TextBundle::default()));
+        });
+}
+
+/// Up and Down step through the history like a shell
+pub(super) fn recall_history(
+    keys: Res<Input<KeyCode>>,
+    focus: Res<CommandFocus>,
+    mut history: ResMut<CommandHistory>,
+    mut command_input: ResMut<CommandInput>,
+) {
+    if !focus.focused {
+        return;
+    }
+
+    let len = history.entries.len();
+    let entries = &history.entries;
+    let is_own = |index: &usize| entries[*index].by.is_none();
+
+    let recalled = if keys.just_pressed(KeyCode::Up) {
+        let older = (0..history.recalled.unwrap_or(len)).rev().find(is_own);
+
+        match (history.recalled, older) {
+            (None, None) => return,
+            (None, Some(index)) => {
+                history.draft = command_input.text().to_string();
+                Some(index)
+            }
+            // Stays on the oldest
+            (Some(index), older) => Some(older.unwrap_or(index)),
+        }
+    } else if keys.just_pressed(KeyCode::Down) {
+        match history.recalled {
+            None => return,
+            Some(index) => (index + 1..len).find(is_own),
+        }
+    } else {
+        return;
+    };
+
+    history.recalled = recalled;
+    match recalled {
+        Some(index) => command_input.set(history.entries[index].input.clone()),
+        None => command_input.set(std::mem::take(&mut history.draft)),
+    }
+}
+
+pub(super) fn record_history(
+    mut history: ResMut<CommandHistory>,
+    local: Res<LocalSide>,
+    mut ev_local: EventReader<LocalCommand>,
+    mut ev_executed: EventReader<CommandExecuted>,
+    mut ev_rejected: EventReader<CommandRejected>,
+) {
+    if ev_local.iter().last().is_some() {
+        history.recalled = None;
+        history.draft.clear();
+    }
+
+    for ev in ev_executed.iter() {
+        history.push(HistoryEntry {
+            input: ev.input.clone(),
+            outcome: Outcome::Executed,
+            output: ev.output.clone(),
+            by: (ev.side != local.0).then_some(ev.side),
+        });
+    }
+
+    for ev in ev_rejected.iter() {
+        history.push(HistoryEntry {
+            input: ev.input.clone(),
+            outcome: Outcome::Rejected(ev.reason.to_string()),
+            output: None,
+            by: (ev.side != local.0).then_some(ev.side),
+        });
+    }
+}
+
+/// Mouse wheel over the log, or Page Up/Down anywhere, scrolls back through older entries
+pub(super) fn scroll_history(
+    keys: Res<Input<KeyCode>>,
+    mut ev_wheel: EventReader<MouseWheel>,
+    mut history: ResMut<CommandHistory>,
+    query: Query<&Interaction, With<HistoryPanel>>,
+) {
+    let is_hovered = query.iter().any(|i| *i != Interaction::None);
+    let wheel: f32 = ev_wheel.iter().map(|ev| ev.y).sum();
+
+    let mut delta = 0;
+
+    if is_hovered && wheel != 0.0 {
+        delta += wheel.signum() as isize;
+    }
+    if keys.just_pressed(KeyCode::PageUp) {
+        delta += LOG_LINES as isize;
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        delta -= LOG_LINES as isize;
+    }
+
+    if delta == 0 {
+        return;
+    }
+
+    let max = history.entries.len().saturating_sub(LOG_LINES) as isize;
+    history.scroll = (history.scroll as isize + delta).clamp(0, max) as usize;
+}
+
+pub(super) fn render_history(
+    asset_server: Res<AssetServer>,
+    history: Res<CommandHistory>,
+    mut query: Query<&mut Text, With<HistoryLog>>,
+) {
+    if !history.is_changed() {
+        return;
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let end = history.entries.len() - history.scroll.min(history.entries.len());
+    let start = end.saturating_sub(LOG_LINES);
+
+    let section = |value: String, color: Color| {
+        TextSection::new(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size: 16.0,
+                color,
+            },
+        )
+    };
+
+    let mut sections = vec![];
+
+    for entry in history.entries[start..end].iter() {
+        let input = match entry.by {
+            Some(side) => format!("{}: {}", side, entry.input.to_uppercase()),
+            None => entry.input.to_uppercase(),
+        };
+
+        sections.push(match &entry.outcome {
+            Outcome::Executed => section(format!("{}\n", input), EXECUTED_COLOR),
+            Outcome::Rejected(reason) => {
+                section(format!("{}  {}\n", input, reason), REJECTED_COLOR)
+            }
+        });
+
+        if let Some(output) = &entry.output {
+            sections.push(section(format!("{}\n", output), OUTPUT_COLOR));
+        }
+    }
+
+    if let Ok(mut text) = query.get_single_mut() {
+        text.sections = sections;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(input: &str, outcome: Outcome) -> HistoryEntry {
+        HistoryEntry {
+            input: input.to_string(),
+            outcome,
+            output: None,
+            by: None,
+        }
+    }
+
+    /// A fresh file under the temp dir, removed when the test is done with it
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "{}_{}_{}",
+                env!("CARGO_PKG_NAME"),
+                std::process::id(),
+                name
+            ));
+            let _ = fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn lines_round_trip() {
+        let entries = [
+            entry("b2:c3", Outcome::Executed),
+            entry(
+                "b2:z9",
+                Outcome::Rejected("Z9 is off the board".to_string()),
+            ),
+            entry(
+                "build b2",
+                Outcome::Rejected("Not enough gold\n\tneeds 5, has 2\\n".to_string()),
+            ),
+        ];
+
+        for entry in entries {
+            let line = entry.to_line();
+            assert!(!line.contains('\n'), "{:?}", line);
+            assert_eq!(
+                line.matches('\t').count(),
+                1 + line.starts_with("err") as usize
+            );
+            assert_eq!(HistoryEntry::from_line(&line), Some(entry));
+        }
+    }
+
+    #[test]
+    fn bad_lines_are_skipped() {
+        assert_eq!(HistoryEntry::from_line(""), None);
+        assert_eq!(HistoryEntry::from_line("ok"), None);
+        assert_eq!(HistoryEntry::from_line("ok\tb2:c3\tno reason"), None);
+        assert_eq!(HistoryEntry::from_line("err\tb2:c3"), None);
+        assert_eq!(HistoryEntry::from_line("maybe\tb2:c3"), None);
+    }
+
+    #[test]
+    fn push_keeps_the_newest() {
+        let mut history = CommandHistory::new(3, None);
+
+        for i in 0..5 {
+            history.push(entry(&format!("a{}:b1", i + 1), Outcome::Executed));
+        }
+
+        let inputs: Vec<_> = history.entries.iter().map(|e| e.input.as_str()).collect();
+        assert_eq!(inputs, ["a3:b1", "a4:b1", "a5:b1"]);
+    }
+
+    #[test]
+    fn saves_and_trims_the_file_on_load() {
+        let file = TempFile::new("history_trim.txt");
+
+        let mut history = CommandHistory::new(10, Some(file.0.clone()));
+        for i in 0..5 {
+            history.push(entry(&format!("a{}:b1", i + 1), Outcome::Executed));
+        }
+        history.push(entry("a1:z9", Outcome::Rejected("two\nlines".to_string())));
+        // The other side's commands aren't saved
+        history.push(HistoryEntry {
+            by: Some(Side::Attacker),
+            ..entry("c1:c2", Outcome::Executed)
+        });
+
+        let mut loaded = CommandHistory::new(3, Some(file.0.clone()));
+        loaded.load();
+
+        let expected = [
+            entry("a4:b1", Outcome::Executed),
+            entry("a5:b1", Outcome::Executed),
+            entry("a1:z9", Outcome::Rejected("two\nlines".to_string())),
+        ];
+        assert_eq!(loaded.entries, expected);
+
+        // Trimmed on disk too
+        let contents = fs::read_to_string(&file.0).unwrap();
+        assert_eq!(contents.lines().count(), 3);
+
+        let mut reloaded = CommandHistory::new(10, Some(file.0.clone()));
+        reloaded.load();
+        assert_eq!(reloaded.entries, expected);
+    }
+}
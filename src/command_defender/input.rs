@@ -0,0 +1,666 @@
+use std::ops::Range;
+
+use bevy::{prelude::*, window::WindowFocused};
+
+use super::{
+    autocomplete::Suggestions,
+    console::{self, ConsoleState},
+    CommandBar, InputField, LocalCommand,
+};
+
+/// Seconds a key has to be held before it starts repeating
+const REPEAT_DELAY: f32 = 0.45;
+/// Seconds between repeats while held
+const REPEAT_RATE: f32 = 0.035;
+/// Seconds the caret stays on, then off
+const CARET_BLINK: f32 = 0.5;
+
+const TEXT_COLOR: Color = Color::WHITE;
+const SELECTED_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
+const PLACEHOLDER_COLOR: Color = Color::GRAY;
+const GHOST_COLOR: Color = Color::DARK_GRAY;
+
+/// Text in the command bar with a caret and an optional selection. Positions are
+/// character indices, not bytes
+#[derive(Resource, Debug, Default)]
+pub(super) struct CommandInput {
+    text: String,
+    caret: usize,
+    /// Other end of the selection, the caret is always one end
+    anchor: Option<usize>,
+}
+
+impl CommandInput {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text and puts the caret at the end
+    pub fn set(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.caret = self.len();
+        self.anchor = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self, index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    /// Selected characters, `None` when nothing is selected
+    pub fn selection(&self) -> Option<Range<usize>> {
+        match self.anchor {
+            Some(anchor) if anchor != self.caret => {
+                Some(anchor.min(self.caret)..anchor.max(self.caret))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection()
+            .map(|range| &self.text[self.byte_index(range.start)..self.byte_index(range.end)])
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.caret = self.len();
+    }
+
+    /// Removes the characters in `range` and puts the caret where they were
+    fn remove(&mut self, range: Range<usize>) {
+        let bytes = self.byte_index(range.start)..self.byte_index(range.end);
+
+        self.text.replace_range(bytes, "");
+        self.caret = range.start;
+        self.anchor = None;
+    }
+
+    /// Removes the selection, returns `false` if nothing was selected
+    fn remove_selection(&mut self) -> bool {
+        match self.selection() {
+            Some(range) => {
+                self.remove(range);
+                true
+            }
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    /// Inserts at the caret, replacing the selection
+    pub fn insert(&mut self, text: &str) {
+        self.remove_selection();
+
+        let at = self.byte_index(self.caret);
+        self.text.insert_str(at, text);
+        self.caret += text.chars().count();
+    }
+
+    /// Inserts the characters `accepts` allows, so pasting several lines or a tab
+    /// separated history line doesn't break the bar
+    pub fn paste(&mut self, text: &str, accepts: fn(char) -> bool) {
+        let pasted: String = text.chars().filter(|c| accepts(*c)).collect();
+        self.insert(&pasted);
+    }
+
+    /// Replaces the characters in `range` and puts the caret after the new text
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        self.move_to(range.start, false);
+        self.move_to(range.end, true);
+        self.insert(text);
+    }
+
+    pub fn delete_backward(&mut self, word: bool) {
+        if self.remove_selection() || self.caret == 0 {
+            return;
+        }
+
+        let start = match word {
+            true => self.word_start(self.caret),
+            false => self.caret - 1,
+        };
+
+        self.remove(start..self.caret);
+    }
+
+    pub fn delete_forward(&mut self, word: bool) {
+        if self.remove_selection() || self.caret == self.len() {
+            return;
+        }
+
+        let end = match word {
+            true => self.word_end(self.caret),
+            false => self.caret + 1,
+        };
+
+        self.remove(self.caret..end);
+    }
+
+    /// Moves the caret, with `select` the selection grows or shrinks with it
+    pub fn move_to(&mut self, index: usize, select: bool) {
+        match select {
+            true => {
+                self.anchor.get_or_insert(self.caret);
+            }
+            false => self.anchor = None,
+        }
+
+        self.caret = index.min(self.len());
+    }
+
+    pub fn move_left(&mut self, word: bool, select: bool) {
+        // Without shift an active selection collapses to its start
+        let index = match (self.selection(), select, word) {
+            (Some(range), false, false) => range.start,
+            (_, _, true) => self.word_start(self.caret),
+            _ => self.caret.saturating_sub(1),
+        };
+
+        self.move_to(index, select);
+    }
+
+    pub fn move_right(&mut self, word: bool, select: bool) {
+        let index = match (self.selection(), select, word) {
+            (Some(range), false, false) => range.end,
+            (_, _, true) => self.word_end(self.caret),
+            _ => self.caret + 1,
+        };
+
+        self.move_to(index, select);
+    }
+
+    /// Start of the word before `index`, skipping any separators right before it
+    fn word_start(&self, index: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = index;
+
+        while i > 0 && !is_word_char(chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && is_word_char(chars[i - 1]) {
+            i -= 1;
+        }
+
+        i
+    }
+
+    /// End of the word after `index`, skipping any separators right after it
+    fn word_end(&self, index: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut i = index;
+
+        while i < chars.len() && !is_word_char(chars[i]) {
+            i += 1;
+        }
+        while i < chars.len() && is_word_char(chars[i]) {
+            i += 1;
+        }
+
+        i
+    }
+}
+
+/// Letters and numbers make up words, `:`, `-` and spaces separate them
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+}
+
+/// Characters the command bar accepts
+pub(super) fn is_command_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == ':' || c == '-' || c == ' '
+}
+
+/// Whether keys and cell clicks go to the command bar. It starts focused, Escape or
+/// leaving the window takes the focus away and clicking the bar gives it back
+#[derive(Resource, Debug)]
+pub(super) struct CommandFocus {
+    pub focused: bool,
+}
+
+impl Default for CommandFocus {
+    fn default() -> Self {
+        Self { focused: true }
+    }
+}
+
+pub(super) fn update_focus(
+    keys: Res<Input<KeyCode>>,
+    mut ev_window_focused: EventReader<WindowFocused>,
+    bar: Query<&Interaction, (Changed<Interaction>, With<CommandBar>)>,
+    mut focus: ResMut<CommandFocus>,
+) {
+    let mut focused = focus.focused;
+
+    for ev in ev_window_focused.iter() {
+        focused = ev.focused;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        focused = false;
+    }
+
+    if bar
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        focused = true;
+    }
+
+    if focus.focused != focused {
+        focus.focused = focused;
+    }
+}
+
+/// Editing keys that repeat while held
+const REPEATING_KEYS: [KeyCode; 4] = [
+    KeyCode::Back,
+    KeyCode::Delete,
+    KeyCode::Left,
+    KeyCode::Right,
+];
+
+/// Our own key repeat for the editing keys, `Input::just_pressed` only fires once per press
+#[derive(Resource)]
+pub(super) struct KeyRepeat {
+    key: Option<KeyCode>,
+    timer: Timer,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            key: None,
+            timer: Timer::from_seconds(REPEAT_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+impl KeyRepeat {
+    /// Keys that should act this frame, the first press and every repeat after the delay
+    fn triggered(&mut self, keys: &Input<KeyCode>, time: &Time) -> Vec<KeyCode> {
+        let mut triggered: Vec<KeyCode> = REPEATING_KEYS
+            .iter()
+            .copied()
+            .filter(|key| keys.just_pressed(*key))
+            .collect();
+
+        // The most recent press is the one that repeats
+        if let Some(key) = triggered.last() {
+            self.key = Some(*key);
+            self.timer = Timer::from_seconds(REPEAT_DELAY, TimerMode::Once);
+            return triggered;
+        }
+
+        let key = match self.key {
+            Some(key) if keys.pressed(key) => key,
+            _ => {
+                self.key = None;
+                return triggered;
+            }
+        };
+
+        self.timer.tick(time.delta());
+
+        if self.timer.finished() {
+            triggered.push(key);
+            self.timer = Timer::from_seconds(REPEAT_RATE, TimerMode::Once);
+        }
+
+        triggered
+    }
+}
+
+/// Caret blink, restarted whenever the input changes so the caret shows while typing
+#[derive(Resource)]
+pub(super) struct CaretBlink {
+    timer: Timer,
+    visible: bool,
+}
+
+impl Default for CaretBlink {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(CARET_BLINK, TimerMode::Repeating),
+            visible: true,
+        }
+    }
+}
+
+/// Types into `CommandInput`; press enter to submit the full string
+#[allow(clippy::too_many_arguments)]
+pub(super) fn text_input(
+    mut char_evr: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut repeat: ResMut<KeyRepeat>,
+    console_state: Res<ConsoleState>,
+    focus: Res<CommandFocus>,
+    mut command_input: ResMut<CommandInput>,
+    mut ev_local: EventWriter<LocalCommand>,
+) {
+    if !focus.focused {
+        char_evr.clear();
+        return;
+    }
+
+    let accepts = match console_state.open {
+        true => console::is_console_char,
+        false => is_command_char,
+    };
+
+    if keys.just_pressed(KeyCode::Return) {
+        ev_local.send(LocalCommand(command_input.text().to_string()));
+        command_input.clear();
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    // Cmd on macOS
+    let ctrl = keys.any_pressed([
+        KeyCode::LControl,
+        KeyCode::RControl,
+        KeyCode::LWin,
+        KeyCode::RWin,
+    ]);
+    // Alt is the word modifier on macOS
+    let word = ctrl || keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]);
+
+    for key in repeat.triggered(&keys, &time) {
+        match key {
+            KeyCode::Back => command_input.delete_backward(word),
+            KeyCode::Delete => command_input.delete_forward(word),
+            KeyCode::Left => command_input.move_left(word, shift),
+            KeyCode::Right => command_input.move_right(word, shift),
+            _ => {}
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Home) {
+        command_input.move_to(0, shift);
+    }
+    if keys.just_pressed(KeyCode::End) {
+        command_input.move_to(usize::MAX, shift);
+    }
+
+    if ctrl {
+        if keys.just_pressed(KeyCode::A) {
+            command_input.select_all();
+        }
+        if keys.just_pressed(KeyCode::C) || keys.just_pressed(KeyCode::X) {
+            if let Some(selected) = command_input.selected_text() {
+                set_clipboard(selected);
+
+                // Without a selection this would delete the character before the cursor
+                if keys.just_pressed(KeyCode::X) {
+                    command_input.delete_backward(false);
+                }
+            }
+        }
+        if keys.just_pressed(KeyCode::V) {
+            command_input.paste(&get_clipboard(), accepts);
+        }
+
+        // Shortcuts also come through as characters, don't type them
+        char_evr.clear();
+        return;
+    }
+
+    for ev in char_evr.iter() {
+        if accepts(ev.char) {
+            command_input.insert(&ev.char.to_string());
+        }
+    }
+}
+
+fn get_clipboard() -> String {
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+        Ok(text) => text,
+        Err(error) => {
+            warn!("Couldn't read the clipboard: {}", error);
+            String::new()
+        }
+    }
+}
+
+fn set_clipboard(text: &str) {
+    let result = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+
+    if let Err(error) = result {
+        warn!("Couldn't write to the clipboard: {}", error);
+    }
+}
+
+/// Draws the text with the selection highlighted, a blinking caret and the rest of
+/// the selected suggestion as ghost text
+pub(super) fn render_text_input(
+    command_input: Res<CommandInput>,
+    suggestions: Res<Suggestions>,
+    console_state: Res<ConsoleState>,
+    focus: Res<CommandFocus>,
+    time: Res<Time>,
+    mut blink: ResMut<CaretBlink>,
+    mut query: Query<&mut Text, With<InputField>>,
+) {
+    if command_input.is_changed() || suggestions.is_changed() || focus.is_changed() {
+        blink.timer.reset();
+        blink.visible = true;
+    } else {
+        blink.timer.tick(time.delta());
+
+        if blink.timer.times_finished_this_tick().is_multiple_of(2) {
+            return;
+        }
+
+        blink.visible = !blink.visible;
+    }
+
+    let mut text = query.single_mut();
+    let style = text.sections[0].style.clone();
+
+    let section = |value: String, color: Color| TextSection {
+        value,
+        style: TextStyle {
+            color,
+            ..style.clone()
+        },
+    };
+    // No caret at all without focus
+    let caret = section(
+        "|".to_string(),
+        match blink.visible && focus.focused {
+            true => TEXT_COLOR,
+            false => Color::NONE,
+        },
+    );
+
+    if command_input.is_empty() {
+        let placeholder = match console_state.open {
+            true => "console: get PanCam.min_scale",
+            false => "A1:A2",
+        };
+
+        text.sections = vec![caret, section(placeholder.to_string(), PLACEHOLDER_COLOR)];
+        return;
+    }
+
+    // Console lines name types and fields, so they keep their case
+    let chars: Vec<char> = command_input
+        .text()
+        .chars()
+        .map(|c| match console_state.open {
+            true => c,
+            false => c.to_ascii_uppercase(),
+        })
+        .collect();
+    let selection = command_input.selection().unwrap_or(0..0);
+    let mut sections = vec![];
+    let mut start = 0;
+
+    // Split wherever the selection starts or ends and where the caret sits
+    let mut splits = vec![
+        selection.start,
+        selection.end,
+        command_input.caret(),
+        chars.len(),
+    ];
+    splits.sort_unstable();
+    splits.dedup();
+
+    for end in splits {
+        if end > start {
+            let color = match selection.contains(&start) {
+                true => SELECTED_COLOR,
+                false => TEXT_COLOR,
+            };
+
+            sections.push(section(chars[start..end].iter().collect(), color));
+            start = end;
+        }
+
+        if end == command_input.caret() {
+            sections.push(caret.clone());
+
+            if let Some(ghost) = suggestions.ghost() {
+                sections.push(section(ghost.to_ascii_uppercase(), GHOST_COLOR));
+            }
+        }
+    }
+
+    text.sections = sections;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str, caret: usize) -> CommandInput {
+        let mut input = CommandInput::default();
+        input.set(text);
+        input.move_to(caret, false);
+        input
+    }
+
+    fn input_at_end(text: &str) -> CommandInput {
+        typed(text, usize::MAX)
+    }
+
+    #[test]
+    fn insert_replaces_the_selection() {
+        let mut input = typed("a1:b2", 3);
+        input.move_to(5, true);
+        assert_eq!(input.selected_text(), Some("b2"));
+
+        input.insert("c3");
+        assert_eq!(input.text(), "a1:c3");
+        assert_eq!(input.caret(), 5);
+        assert_eq!(input.selection(), None);
+
+        // Selected right to left
+        let mut input = typed("build a1", 8);
+        input.move_to(6, true);
+        input.insert("x");
+        assert_eq!(input.text(), "build x");
+        assert_eq!(input.caret(), 7);
+    }
+
+    #[test]
+    fn moves_by_word() {
+        let mut input = typed("build a1-b2", 11);
+
+        input.move_left(true, false);
+        assert_eq!(input.caret(), 9);
+        input.move_left(true, false);
+        assert_eq!(input.caret(), 6);
+        input.move_left(true, false);
+        assert_eq!(input.caret(), 0);
+        input.move_left(true, false);
+        assert_eq!(input.caret(), 0);
+
+        input.move_right(true, false);
+        assert_eq!(input.caret(), 5);
+        input.move_right(true, true);
+        assert_eq!(input.caret(), 8);
+        assert_eq!(input.selected_text(), Some(" a1"));
+
+        // Without shift the selection collapses instead of moving
+        input.move_left(false, false);
+        assert_eq!(input.caret(), 5);
+        assert_eq!(input.selection(), None);
+    }
+
+    #[test]
+    fn deletes_by_word() {
+        let mut input = typed("build a1-b2", 11);
+
+        input.delete_backward(true);
+        assert_eq!(input.text(), "build a1-");
+        input.delete_backward(true);
+        assert_eq!(input.text(), "build ");
+
+        input.move_to(0, false);
+        input.delete_forward(true);
+        assert_eq!(input.text(), " ");
+    }
+
+    #[test]
+    fn backspace_at_the_start_does_nothing() {
+        let mut input = typed("a1", 0);
+
+        input.delete_backward(false);
+        input.delete_backward(true);
+        assert_eq!(input.text(), "a1");
+        assert_eq!(input.caret(), 0);
+
+        let mut input = input_at_end("a1");
+        input.delete_forward(false);
+        assert_eq!(input.text(), "a1");
+    }
+
+    #[test]
+    fn paste_strips_what_cant_be_typed() {
+        let mut input = input_at_end("a1:");
+        input.paste("b2\n", is_command_char);
+        assert_eq!(input.text(), "a1:b2");
+
+        let mut input = input_at_end("");
+        input.paste("ok\tA1:A2\r\nB1:B2", is_command_char);
+        assert_eq!(input.text(), "okA1:A2B1:B2");
+
+        // The console takes more, but still no control characters
+        let mut input = input_at_end("");
+        input.paste("set PanCam.min_scale\t0.5\n", console::is_console_char);
+        assert_eq!(input.text(), "set PanCam.min_scale0.5");
+    }
+
+    #[test]
+    fn positions_are_characters() {
+        let mut input = input_at_end("a1");
+        input.insert("é");
+        assert_eq!(input.caret(), 3);
+
+        input.delete_backward(false);
+        assert_eq!(input.text(), "a1");
+        assert_eq!(input.caret(), 2);
+    }
+}
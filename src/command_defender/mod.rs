@@ -0,0 +1,599 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, time::FixedTimestep};
+
+use crate::interaction::WorldInteractionSystemLabel;
+
+pub mod ai;
+pub mod autocomplete;
+pub mod board;
+mod click;
+pub mod console;
+pub mod fog;
+pub mod game;
+pub mod history;
+mod input;
+pub mod net;
+pub mod parser;
+pub mod script;
+pub mod undo;
+pub mod verbs;
+
+pub use board::Board;
+pub use history::CommandHistory;
+
+use input::CommandInput;
+pub use parser::{GridCommand, GridCoord, GridRange, ParseError};
+pub use verbs::{AddVerb, ArgKind, VerbRegistry, VerbSpec};
+
+/// Command driven tower defense on a lettered board. Clicking cells goes through
+/// `WorldInteractionPlugin`, so it and the plugins it builds on need to be added too
+pub struct CommandDefenderPlugin {
+    pub columns: u32,
+    pub rows: u32,
+    /// Width and height of a cell in world units
+    pub cell_size: f32,
+    /// Cells that start with a player unit in them
+    pub starting_units: Vec<GridCoord>,
+    /// Commands kept in the history
+    pub history_limit: usize,
+    /// Where the history is saved, `None` to not save it
+    pub history_path: Option<PathBuf>,
+    /// Seconds between game ticks, enemies move and spawn once per tick
+    pub tick_seconds: f64,
+    pub waves: u32,
+    pub base_health: u32,
+    pub starting_gold: u32,
+    /// Competitive games can't be undone
+    pub mode: game::GameMode,
+    /// Board actions kept for `undo`
+    pub undo_limit: usize,
+    /// Backtick opens the dev console in the command bar, on in debug builds
+    pub dev_console: bool,
+    /// Only runs the game, without any UI or keyboard input. See `script::run_headless`
+    pub headless: bool,
+    /// Side the keyboard and scripts play
+    pub side: Side,
+    /// Commands can only target cells the side's pieces see, off by default
+    pub fog_of_war: bool,
+    /// Seeds where enemies spawn, `None` picks a random seed
+    pub wave_seed: Option<u64>,
+    /// Lets the computer command the attacker when the keyboard plays the defender
+    pub opponent: Option<ai::ComputerOpponent>,
+}
+
+impl Default for CommandDefenderPlugin {
+    fn default() -> Self {
+        Self {
+            columns: 12,
+            rows: 8,
+            cell_size: 64.0,
+            starting_units: vec![
+                GridCoord::new(0, 2),
+                GridCoord::new(0, 3),
+                GridCoord::new(0, 4),
+                GridCoord::new(0, 5),
+            ],
+            history_limit: 100,
+            history_path: CommandHistory::default_path(),
+            tick_seconds: 1.0,
+            waves: 5,
+            base_health: 10,
+            starting_gold: 5,
+            mode: game::GameMode::Casual,
+            undo_limit: 50,
+            dev_console: cfg!(debug_assertions),
+            headless: false,
+            side: Side::Defender,
+            fog_of_war: false,
+            wave_seed: None,
+            opponent: None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct InputField;
+
+/// The bar around `InputField`, clicking it focuses the input
+#[derive(Component)]
+struct CommandBar;
+
+/// Who a command comes from. The defender holds the base on the left with `Unit`s, the
+/// attacker commands the `Enemy` waves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Side {
+    #[default]
+    Defender,
+    Attacker,
+}
+
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Defender => write!(f, "defender"),
+            Side::Attacker => write!(f, "attacker"),
+        }
+    }
+}
+
+/// Side played on this machine, through the keyboard or a script
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct LocalSide(pub Side);
+
+/// A line of text to run as a command. Sent for every `LocalCommand`, anything else
+/// (the computer opponent, the other player's turns) can send it too
+#[derive(Debug, Clone)]
+pub struct SubmitCommand {
+    pub input: String,
+    pub side: Side,
+    /// A dev console line typed on this machine, run as is instead of parsed
+    pub console: bool,
+}
+
+impl SubmitCommand {
+    pub fn new(input: impl Into<String>, side: Side) -> Self {
+        Self {
+            input: input.into(),
+            side,
+            console: false,
+        }
+    }
+
+    pub fn console(input: impl Into<String>, side: Side) -> Self {
+        Self {
+            console: true,
+            ..Self::new(input, side)
+        }
+    }
+}
+
+/// A line typed or scripted on this machine for the `LocalSide`. Submitted straight away,
+/// or by `NetworkPlugin` once both players agreed on the turn it's in
+#[derive(Debug, Clone)]
+pub struct LocalCommand(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `B2:C3`
+    Move(GridCommand),
+    /// A registered verb, eg. `attack B2`
+    Verb(verbs::VerbArgs),
+    /// A line typed while the dev console is open, eg. `get PanCam.min_scale`
+    Console(String),
+}
+
+impl Command {
+    /// A line is a verb when its first word is a registered verb or only letters,
+    /// anything else has to be a move
+    pub fn parse(input: &str, verbs: &VerbRegistry) -> Result<Command, RejectReason> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+
+        if let Some(first) = words.first() {
+            if let Some(spec) = verbs.get(first) {
+                return spec
+                    .parse(&words[1..])
+                    .map(Command::Verb)
+                    .map_err(RejectReason::Arguments);
+            }
+
+            if first.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(RejectReason::UnknownVerb(first.to_ascii_lowercase()));
+            }
+        }
+
+        input
+            .parse()
+            .map(Command::Move)
+            .map_err(RejectReason::Parse)
+    }
+
+    /// Cells on `board` the command is aimed at. For a move that's where every cell of
+    /// `from` would end up, the same offset `Board::plan_move` shifts them by. Ranges are
+    /// clamped to the board, so there are never more than it has cells
+    pub fn targets(&self, board: &Board) -> Vec<GridCoord> {
+        let on_board = |range: GridRange| board.clamp(range).into_iter().flat_map(|r| r.cells());
+
+        match self {
+            Command::Move(command) => {
+                let start = command.from.start;
+                let offset = |from: u32, start: u32, to: u32| {
+                    u32::try_from(from as i64 - start as i64 + to as i64).ok()
+                };
+
+                on_board(command.from)
+                    .filter_map(|coord| {
+                        let column = offset(coord.column, start.column, command.to.column)?;
+                        let row = offset(coord.row, start.row, command.to.row)?;
+                        Some(GridCoord::new(column, row))
+                    })
+                    .filter(|coord| board.contains(*coord))
+                    .collect()
+            }
+            Command::Verb(args) => args
+                .values
+                .iter()
+                .flatten()
+                .flat_map(|value| match value {
+                    verbs::ArgValue::Coord(coord) => on_board(GridRange::single(*coord)).collect(),
+                    verbs::ArgValue::Range(range) => on_board(*range).collect(),
+                    _ => vec![],
+                })
+                .collect(),
+            Command::Console(_) => vec![],
+        }
+    }
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Move(command) => write!(f, "{}", command),
+            Command::Verb(args) => write!(f, "{}", args),
+            Command::Console(line) => write!(f, "{}", line),
+        }
+    }
+}
+
+/// A submitted command that parsed, whatever handles it can still reject it
+#[derive(Debug, Clone)]
+pub struct CommandIssued {
+    pub input: String,
+    pub side: Side,
+    pub command: Command,
+}
+
+/// A command that was carried out
+#[derive(Debug, Clone)]
+pub struct CommandExecuted {
+    pub input: String,
+    pub side: Side,
+    pub command: Command,
+    /// Anything to show the player, eg. the `help` text
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandRejected {
+    pub input: String,
+    pub side: Side,
+    pub reason: RejectReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectReason {
+    Parse(ParseError),
+    OffBoard(GridCoord),
+    Occupied(GridCoord),
+    /// Nothing in the range can be moved
+    NoUnits(GridRange),
+    /// Enemies only step into the row above or below
+    LaneChange,
+    /// The side's pieces can't see the cell
+    NoVision(GridCoord),
+    UnknownVerb(String),
+    Arguments(verbs::ArgError),
+    /// The verb's handler turned it down
+    Verb(String),
+    Console(String),
+    /// The other player left or the games went out of sync
+    Network(String),
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RejectReason::Parse(error) => write!(f, "{}", error),
+            RejectReason::OffBoard(coord) => write!(f, "{} is off the board", coord),
+            RejectReason::Occupied(coord) => write!(f, "{} is already occupied", coord),
+            RejectReason::NoUnits(range) => write!(f, "no units to move in {}", range),
+            RejectReason::LaneChange => {
+                write!(f, "enemies can only change lanes, one row up or down")
+            }
+            RejectReason::NoVision(coord) => write!(f, "no vision of {}", coord),
+            RejectReason::UnknownVerb(verb) => {
+                write!(f, "unknown command '{}', type help for the list", verb)
+            }
+            RejectReason::Arguments(error) => write!(f, "{}", error),
+            RejectReason::Verb(reason) => write!(f, "{}", reason),
+            RejectReason::Console(reason) => write!(f, "{}", reason),
+            RejectReason::Network(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum Systems {
+    RecordInput,
+    Parse,
+    Execute,
+    /// Verb handlers, after the verbs are routed to them
+    Handle,
+}
+
+impl Plugin for CommandDefenderPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = game::GameSettings {
+            waves: self.waves,
+            base_health: self.base_health,
+            starting_gold: self.starting_gold,
+        };
+
+        app.init_resource::<CommandInput>()
+            .init_resource::<input::KeyRepeat>()
+            .init_resource::<input::CaretBlink>()
+            .init_resource::<input::CommandFocus>()
+            .init_resource::<autocomplete::Suggestions>()
+            .init_resource::<console::ConsoleState>()
+            .insert_resource(LocalSide(self.side))
+            .insert_resource(Board::new(self.columns, self.rows, self.cell_size))
+            .insert_resource(board::StartingUnits(self.starting_units.clone()))
+            .insert_resource(CommandHistory::new(
+                self.history_limit,
+                self.history_path.clone(),
+            ))
+            .add_event::<LocalCommand>()
+            .add_event::<SubmitCommand>()
+            .add_event::<CommandIssued>()
+            .add_event::<CommandExecuted>()
+            .add_event::<CommandRejected>()
+            .insert_resource(undo::UndoHistory::new(
+                self.undo_limit,
+                self.mode != game::GameMode::Competitive,
+            ))
+            .insert_resource(settings.stats())
+            .register_type::<game::GameStats>()
+            .insert_resource(game::Waves::new(settings.waves))
+            .insert_resource(game::WaveRng::new(self.wave_seed))
+            .insert_resource(fog::FogOfWar::new(self.fog_of_war))
+            .register_type::<fog::FogOfWar>()
+            .insert_resource(settings)
+            .add_state(game::GameState::Playing)
+            .add_verb::<verbs::Help>(VerbSpec::new("help", "list the commands"))
+            .add_verb::<game::Attack>(
+                VerbSpec::new("attack", "units in range shoot the enemy")
+                    .arg("target", ArgKind::Coord),
+            )
+            .add_verb::<game::Build>(
+                VerbSpec::new("build", "spend gold on a unit or wall near the base")
+                    .arg("cell", ArgKind::Coord)
+                    .arg("kind", ArgKind::OneOf(game::BUILD_KINDS)),
+            )
+            .add_verb::<game::Restart>(VerbSpec::new("restart", "start a new game"))
+            .add_verb::<undo::Undo>(VerbSpec::new("undo", "take back the last move or build"))
+            .add_verb::<undo::Redo>(VerbSpec::new("redo", "put back what undo took back"))
+            .add_stage_after(
+                CoreStage::Update,
+                game::GameTickStage,
+                SystemStage::single_threaded()
+                    .with_run_criteria(FixedTimestep::step(self.tick_seconds))
+                    .with_system(game::reload_units)
+                    .with_system(game::advance_enemies.after(game::reload_units))
+                    .with_system(game::spawn_waves.after(game::advance_enemies))
+                    .with_system(game::check_outcome.after(game::spawn_waves)),
+            )
+            .add_startup_system(board::spawn_starting_units)
+            .add_startup_system(history::load_history)
+            .add_system(console::run_console.at_end())
+            .add_system(fog::update_fog.before(Systems::Parse))
+            .add_system(
+                submit_local_commands
+                    .after(Systems::RecordInput)
+                    .before(Systems::Parse),
+            )
+            .add_system(
+                parse_commands
+                    .label(Systems::Parse)
+                    .after(Systems::RecordInput),
+            )
+            .add_system(
+                board::execute_moves
+                    .label(Systems::Execute)
+                    .after(Systems::Parse),
+            )
+            .add_system(board::sync_board.after(Systems::Execute))
+            .add_system(board::animate_moves.after(board::sync_board))
+            .add_system_set(
+                SystemSet::new()
+                    .label(Systems::Handle)
+                    .after(Systems::Execute)
+                    .with_system(verbs::help)
+                    .with_system(game::attack)
+                    .with_system(game::build)
+                    .with_system(game::restart)
+                    .with_system(undo::undo_redo),
+            )
+            .add_system(game::show_damage.after(Systems::Handle))
+            .add_system(log_commands.after(Systems::Handle))
+            .add_system(history::record_history.after(Systems::Handle));
+
+        // The computer only knows how to attack
+        match &self.opponent {
+            Some(_) if self.side == Side::Attacker => {
+                warn!("The computer opponent only plays the attacker, leaving it out")
+            }
+            Some(opponent) => {
+                app.insert_resource(ai::Computer::new(opponent))
+                    .add_system_to_stage(
+                        game::GameTickStage,
+                        ai::computer_turn
+                            .after(game::spawn_waves)
+                            .before(game::check_outcome),
+                    );
+            }
+            None => {}
+        }
+
+        // Headless there's no window to draw in or type into, commands come from a script
+        if self.headless {
+            return;
+        }
+
+        app.add_startup_system(setup)
+            .add_startup_system(board::spawn_board)
+            .add_startup_system(history::spawn_history_log)
+            .add_startup_system(autocomplete::spawn_suggestion_list)
+            .add_startup_system(game::spawn_hud)
+            .add_startup_system(fog::spawn_fog)
+            .add_system(
+                autocomplete::accept_suggestion
+                    .after(input::update_focus)
+                    .before(history::recall_history)
+                    .before(Systems::RecordInput),
+            )
+            .add_system(
+                history::recall_history
+                    .after(input::update_focus)
+                    .before(Systems::RecordInput),
+            )
+            .add_system(input::update_focus.before(Systems::RecordInput))
+            .add_system(
+                input::text_input
+                    .label(Systems::RecordInput)
+                    .after(input::update_focus),
+            )
+            .add_system(autocomplete::update_suggestions.after(Systems::RecordInput))
+            .add_system(autocomplete::render_suggestions.after(autocomplete::update_suggestions))
+            .add_system(input::render_text_input.after(autocomplete::update_suggestions))
+            .add_system(
+                undo::undo_keys
+                    .label(Systems::RecordInput)
+                    .after(input::update_focus),
+            )
+            .add_system(click::highlight_cell.after(WorldInteractionSystemLabel))
+            .add_system(
+                click::click_to_fill
+                    .after(WorldInteractionSystemLabel)
+                    .after(input::update_focus)
+                    .before(Systems::RecordInput),
+            )
+            .add_system(game::update_hud.after(Systems::Handle))
+            .add_system(fog::toggle_spectator)
+            .add_system(fog::render_fog.after(fog::toggle_spectator))
+            .add_system(history::scroll_history.after(history::record_history))
+            .add_system(history::render_history.after(history::scroll_history));
+
+        if self.dev_console {
+            app.add_system(console::toggle_console.before(Systems::RecordInput));
+        }
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_field = TextSection {
+        value: "Whats up".to_string(),
+        style: TextStyle {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 24.,
+            color: Color::WHITE,
+        },
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Px(50.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(1., 1., 1., 0.025).into(),
+                ..default()
+            },
+            Interaction::default(),
+            CommandBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle {
+                    text: Text {
+                        sections: vec![text_field],
+                        ..default()
+                    },
+                    ..default()
+                },
+                InputField,
+            ));
+        });
+}
+
+/// In a networked game `net::lockstep` holds them back for the turn instead
+fn submit_local_commands(
+    local: Res<LocalSide>,
+    console: Res<console::ConsoleState>,
+    lockstep: Option<Res<net::Lockstep>>,
+    mut ev_local: EventReader<LocalCommand>,
+    mut ev_submit: EventWriter<SubmitCommand>,
+) {
+    if lockstep.is_some() {
+        ev_local.clear();
+        return;
+    }
+
+    for LocalCommand(input) in ev_local.iter() {
+        ev_submit.send(match console.open {
+            true => SubmitCommand::console(input.clone(), local.0),
+            false => SubmitCommand::new(input.clone(), local.0),
+        });
+    }
+}
+
+fn parse_commands(
+    verbs: Res<VerbRegistry>,
+    board: Res<Board>,
+    fog: Res<fog::FogOfWar>,
+    mut ev_submit: EventReader<SubmitCommand>,
+    mut ev_issued: EventWriter<CommandIssued>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    for SubmitCommand {
+        input,
+        side,
+        console,
+    } in ev_submit.iter()
+    {
+        // Tagged where it was typed, whether the console is open now doesn't matter
+        let command = match console {
+            true => Ok(Command::Console(input.trim().to_string())),
+            false => Command::parse(input, &verbs),
+        };
+        let command = command.and_then(|command| {
+            fog.check(&board, *side, &command)?;
+            Ok(command)
+        });
+
+        match command {
+            Ok(command) => ev_issued.send(CommandIssued {
+                input: input.clone(),
+                side: *side,
+                command,
+            }),
+            Err(reason) => ev_rejected.send(CommandRejected {
+                input: input.clone(),
+                side: *side,
+                reason,
+            }),
+        }
+    }
+}
+
+fn log_commands(
+    mut ev_executed: EventReader<CommandExecuted>,
+    mut ev_rejected: EventReader<CommandRejected>,
+) {
+    for ev in ev_executed.iter() {
+        info!("Command from the {}: {}", ev.side, ev.command);
+
+        if let Some(output) = &ev.output {
+            info!("{}", output);
+        }
+    }
+
+    for ev in ev_rejected.iter() {
+        warn!(
+            "Rejected \"{}\" from the {}: {}",
+            ev.input, ev.side, ev.reason
+        );
+    }
+}
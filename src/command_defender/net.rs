@@ -0,0 +1,777 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+};
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+
+use super::{
+    board::{Board, Unit},
+    console::ConsoleState,
+    game::{Enemy, GameMode, GameStats, GameTickStage, Health, Wall, Waves},
+    Command, CommandDefenderPlugin, CommandRejected, LocalCommand, LocalSide, RejectReason, Side,
+    SubmitCommand, Systems, VerbRegistry,
+};
+
+/// Bumped whenever the messages or the board hash change, both players need the same one
+const PROTOCOL_VERSION: u32 = 2;
+
+/// What the board hash looks at on each piece
+type PieceKind<'a> = (
+    Option<&'a Health>,
+    Option<&'a Unit>,
+    Option<&'a Enemy>,
+    Option<&'a Wall>,
+);
+
+/// Which end of the connection this game is
+#[derive(Debug, Clone)]
+pub enum NetRole {
+    /// Waits for the other player on an address like `127.0.0.1:7777` and plays the defender
+    Host(String),
+    /// Connects to a host and plays the attacker
+    Join(String),
+}
+
+/// One line each, tab separated like the saved history. Commands can't contain tabs
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Message {
+    /// `hello<TAB>version[<TAB>seed]`, the host sends the wave seed
+    Hello { version: u32, seed: Option<u64> },
+    /// `turn<TAB>number<TAB>hash[<TAB>command]...`
+    Turn(Turn),
+}
+
+/// The commands a player gave during a turn, and the hash of the board it started with
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Turn {
+    number: u64,
+    hash: u64,
+    commands: Vec<String>,
+}
+
+impl Message {
+    fn to_line(&self) -> String {
+        match self {
+            Message::Hello {
+                version,
+                seed: Some(seed),
+            } => format!("hello\t{}\t{}", version, seed),
+            Message::Hello {
+                version,
+                seed: None,
+            } => format!("hello\t{}", version),
+            Message::Turn(turn) => {
+                let mut parts = vec![
+                    "turn".to_string(),
+                    turn.number.to_string(),
+                    format!("{:016x}", turn.hash),
+                ];
+                parts.extend(turn.commands.iter().cloned());
+                parts.join("\t")
+            }
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+
+        match parts.as_slice() {
+            ["hello", version] => Some(Message::Hello {
+                version: version.parse().ok()?,
+                seed: None,
+            }),
+            ["hello", version, seed] => Some(Message::Hello {
+                version: version.parse().ok()?,
+                seed: Some(seed.parse().ok()?),
+            }),
+            ["turn", number, hash, commands @ ..] => Some(Message::Turn(Turn {
+                number: number.parse().ok()?,
+                hash: u64::from_str_radix(hash, 16).ok()?,
+                commands: commands.iter().map(|c| c.to_string()).collect(),
+            })),
+            _ => None,
+        }
+    }
+}
+
+fn send(mut stream: &TcpStream, message: &Message) -> io::Result<()> {
+    writeln!(stream, "{}", message.to_line())
+}
+
+fn receive(reader: &mut impl BufRead) -> io::Result<Message> {
+    let mut line = String::new();
+
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Message::from_line(&line).ok_or_else(|| invalid(format!("unexpected message {:?}", line)))
+}
+
+fn invalid(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+/// A connection to the other player that finished the handshake
+pub struct NetSession {
+    writer: TcpStream,
+    reader: BufReader<TcpStream>,
+    pub side: Side,
+    /// Seeds the waves, the host picks it so both games spawn the same enemies
+    pub seed: u64,
+}
+
+impl NetSession {
+    /// Blocks until the other player is connected and agrees on the protocol
+    pub fn connect(role: &NetRole) -> io::Result<Self> {
+        match role {
+            NetRole::Host(address) => {
+                let listener = TcpListener::bind(address)?;
+                // Logging isn't set up yet and stdout is for headless results
+                eprintln!("Waiting for the other player on {}", listener.local_addr()?);
+                Self::host(&listener)
+            }
+            NetRole::Join(address) => Self::join(address),
+        }
+    }
+
+    /// Waits for one player on `listener`, this end plays the defender
+    pub fn host(listener: &TcpListener) -> io::Result<Self> {
+        Self::handshake(listener.accept()?.0, Side::Defender)
+    }
+
+    /// This end plays the attacker
+    pub fn join(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::handshake(TcpStream::connect(address)?, Side::Attacker)
+    }
+
+    /// `game` set up to play this end. Undo isn't part of the turns and would put the
+    /// boards out of sync, so the game is competitive
+    pub fn game_plugin(&self, game: CommandDefenderPlugin) -> CommandDefenderPlugin {
+        CommandDefenderPlugin {
+            side: self.side,
+            wave_seed: Some(self.seed),
+            mode: GameMode::Competitive,
+            ..game
+        }
+    }
+
+    fn handshake(writer: TcpStream, side: Side) -> io::Result<Self> {
+        // Turns are tiny, don't hold them back waiting for more
+        writer.set_nodelay(true)?;
+        let mut reader = BufReader::new(writer.try_clone()?);
+
+        let seed = match side {
+            Side::Defender => {
+                let seed = rand::random();
+                send(
+                    &writer,
+                    &Message::Hello {
+                        version: PROTOCOL_VERSION,
+                        seed: Some(seed),
+                    },
+                )?;
+                check_hello(receive(&mut reader)?)?;
+                seed
+            }
+            Side::Attacker => {
+                let seed = check_hello(receive(&mut reader)?)?
+                    .ok_or_else(|| invalid("the host didn't send a seed".to_string()))?;
+                send(
+                    &writer,
+                    &Message::Hello {
+                        version: PROTOCOL_VERSION,
+                        seed: None,
+                    },
+                )?;
+                seed
+            }
+        };
+
+        Ok(Self {
+            writer,
+            reader,
+            side,
+            seed,
+        })
+    }
+}
+
+/// The seed in the other player's hello, if it's the same protocol
+fn check_hello(message: Message) -> io::Result<Option<u64>> {
+    match message {
+        Message::Hello { version, seed } if version == PROTOCOL_VERSION => Ok(seed),
+        Message::Hello { version, .. } => Err(invalid(format!(
+            "the other player is on protocol {}, this is {}",
+            version, PROTOCOL_VERSION
+        ))),
+        message => Err(invalid(format!("expected a hello, got {:?}", message))),
+    }
+}
+
+/// Plays against another game over TCP in lockstep. Commands typed here are checked for
+/// typos, held until the end of the turn and sent to the other player. Once both players
+/// have sent the turn their commands run on both ends in the same order, defender first,
+/// and then the game tick runs. Each turn carries a hash of the board, when they differ
+/// the games are out of sync and stop.
+///
+/// Needs `CommandDefenderPlugin` added first, set up by the session:
+///
+/// ```ignore
+/// let session = NetSession::connect(&NetRole::Host("127.0.0.1:7777".to_string()))?;
+///
+/// app.add_plugin(session.game_plugin(CommandDefenderPlugin::default()))
+///     .add_plugin(NetworkPlugin::new(session));
+/// ```
+pub struct NetworkPlugin {
+    session: Mutex<Option<NetSession>>,
+    /// Seconds a turn lasts, the slower player sets the pace
+    pub turn_seconds: f32,
+}
+
+impl NetworkPlugin {
+    pub fn new(session: NetSession) -> Self {
+        Self {
+            session: Mutex::new(Some(session)),
+            turn_seconds: 1.0,
+        }
+    }
+}
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        let session = self
+            .session
+            .lock()
+            .unwrap()
+            .take()
+            .expect("NetworkPlugin can only be added once");
+
+        app.insert_resource(Lockstep::new(session, self.turn_seconds))
+            .add_system(lockstep.after(Systems::RecordInput).before(Systems::Parse))
+            // Ticks wait for both players instead of the clock
+            .stage(GameTickStage, |stage: &mut SystemStage| {
+                stage.set_run_criteria(turn_ended)
+            });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetStatus {
+    Playing,
+    /// The other player closed the game or the connection dropped
+    Disconnected,
+    /// The boards differed at the start of this turn
+    Desynced(u64),
+}
+
+impl std::fmt::Display for NetStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetStatus::Playing => write!(f, "playing"),
+            NetStatus::Disconnected => write!(f, "the other player left"),
+            NetStatus::Desynced(turn) => write!(f, "the games went out of sync on turn {}", turn),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct Lockstep {
+    writer: TcpStream,
+    /// Filled from another thread so waiting on the other player doesn't block the app
+    messages: Mutex<Receiver<Message>>,
+    /// Turn being played, counts up from 0
+    pub turn: u64,
+    pub status: NetStatus,
+    timer: Timer,
+    /// Commands typed this turn
+    pending: Vec<String>,
+    /// This turn once it has been sent, until the other player's arrives
+    sent: Option<Turn>,
+    /// Turns from the other player, it can be one ahead
+    received: VecDeque<Turn>,
+    /// A turn just ended, the game tick runs
+    tick: bool,
+}
+
+impl Lockstep {
+    fn new(session: NetSession, turn_seconds: f32) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut reader = session.reader;
+
+        // Dropping the sender when the connection ends tells the app
+        thread::spawn(move || loop {
+            match receive(&mut reader) {
+                Ok(message) => {
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    if error.kind() != io::ErrorKind::UnexpectedEof {
+                        warn!("Lost the other player: {}", error);
+                    }
+                    break;
+                }
+            }
+        });
+
+        Self {
+            writer: session.writer,
+            messages: Mutex::new(receiver),
+            turn: 0,
+            status: NetStatus::Playing,
+            timer: Timer::from_seconds(turn_seconds, TimerMode::Once),
+            pending: vec![],
+            sent: None,
+            received: VecDeque::new(),
+            tick: false,
+        }
+    }
+}
+
+fn turn_ended(mut lockstep: ResMut<Lockstep>) -> ShouldRun {
+    match std::mem::take(&mut lockstep.tick) {
+        true => ShouldRun::Yes,
+        false => ShouldRun::No,
+    }
+}
+
+/// Holds back `LocalCommand`s, trades turns with the other player and submits both
+/// players' commands once a turn is complete
+#[allow(clippy::too_many_arguments)]
+fn lockstep(
+    time: Res<Time>,
+    mut lockstep: ResMut<Lockstep>,
+    local: Res<LocalSide>,
+    console: Res<ConsoleState>,
+    verbs: Res<VerbRegistry>,
+    board: Res<Board>,
+    stats: Res<GameStats>,
+    waves: Res<Waves>,
+    pieces: Query<PieceKind>,
+    mut ev_local: EventReader<LocalCommand>,
+    mut ev_submit: EventWriter<SubmitCommand>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    let lockstep = &mut *lockstep;
+
+    for LocalCommand(input) in ev_local.iter() {
+        // The dev console stays local
+        if console.open {
+            ev_submit.send(SubmitCommand::console(input.clone(), local.0));
+            continue;
+        }
+
+        let checked = match lockstep.status {
+            NetStatus::Playing => Command::parse(input, &verbs).map(|_| ()),
+            status => Err(RejectReason::Network(status.to_string())),
+        };
+
+        match checked {
+            Ok(()) => lockstep.pending.push(input.clone()),
+            Err(reason) => ev_rejected.send(CommandRejected {
+                input: input.clone(),
+                side: local.0,
+                reason,
+            }),
+        }
+    }
+
+    loop {
+        let next = lockstep.messages.lock().unwrap().try_recv();
+
+        match next {
+            Ok(Message::Turn(turn)) => lockstep.received.push_back(turn),
+            Ok(message) => warn!("Ignoring {:?} from the other player", message),
+            Err(TryRecvError::Empty) => break,
+            Err(TryRecvError::Disconnected) => {
+                stop(lockstep, NetStatus::Disconnected, local.0, &mut ev_rejected);
+                break;
+            }
+        }
+    }
+
+    if lockstep.status == NetStatus::Playing {
+        ev_submit.send_batch(play_turn(
+            lockstep,
+            &time,
+            local.0,
+            &mut ev_rejected,
+            || board_hash(&board, &stats, &waves, &pieces),
+        ));
+    }
+}
+
+/// Sends this turn once its time is up and releases it once the other player's arrives
+fn play_turn(
+    lockstep: &mut Lockstep,
+    time: &Time,
+    side: Side,
+    ev_rejected: &mut EventWriter<CommandRejected>,
+    hash: impl FnOnce() -> u64,
+) -> Vec<SubmitCommand> {
+    if lockstep.sent.is_none() && lockstep.timer.tick(time.delta()).finished() {
+        let turn = Turn {
+            number: lockstep.turn,
+            hash: hash(),
+            commands: std::mem::take(&mut lockstep.pending),
+        };
+
+        if let Err(error) = send(&lockstep.writer, &Message::Turn(turn.clone())) {
+            warn!("Couldn't send turn {}: {}", turn.number, error);
+        }
+
+        lockstep.sent = Some(turn);
+    }
+
+    if lockstep.sent.is_none() || lockstep.received.is_empty() {
+        return vec![];
+    }
+
+    let (ours, theirs) = match (lockstep.sent.take(), lockstep.received.pop_front()) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        _ => return vec![],
+    };
+
+    if ours.number != theirs.number || ours.hash != theirs.hash {
+        error!(
+            "Out of sync on turn {}, the other player sent turn {} with hash {:016x}, ours is {:016x}",
+            ours.number, theirs.number, theirs.hash, ours.hash
+        );
+        lockstep.pending.extend(ours.commands);
+        stop(
+            lockstep,
+            NetStatus::Desynced(ours.number),
+            side,
+            ev_rejected,
+        );
+        return vec![];
+    }
+
+    lockstep.turn += 1;
+    lockstep.tick = true;
+    lockstep.timer.reset();
+
+    let (defender, attacker) = match side {
+        Side::Defender => (ours.commands, theirs.commands),
+        Side::Attacker => (theirs.commands, ours.commands),
+    };
+
+    let defender = defender
+        .into_iter()
+        .map(|input| SubmitCommand::new(input, Side::Defender));
+    let attacker = attacker
+        .into_iter()
+        .map(|input| SubmitCommand::new(input, Side::Attacker));
+
+    defender.chain(attacker).collect()
+}
+
+/// Ends the networked game, the commands still waiting on a turn are rejected
+fn stop(
+    lockstep: &mut Lockstep,
+    status: NetStatus,
+    side: Side,
+    ev_rejected: &mut EventWriter<CommandRejected>,
+) {
+    if lockstep.status != NetStatus::Playing {
+        return;
+    }
+
+    lockstep.status = status;
+    warn!("Stopped the networked game, {}", status);
+
+    let waiting = lockstep.sent.take().map(|turn| turn.commands);
+    let pending = std::mem::take(&mut lockstep.pending);
+
+    for input in waiting.into_iter().flatten().chain(pending) {
+        ev_rejected.send(CommandRejected {
+            input,
+            side,
+            reason: RejectReason::Network(status.to_string()),
+        });
+    }
+}
+
+/// 64 bit FNV-1a. The board hash is part of the protocol, so it can't use `DefaultHasher`,
+/// which is free to change between Rust releases
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+}
+
+/// Everything both games have to agree on. Entities are left out, only what's on each
+/// cell counts. Hashed as little endian `u32`s, for every occupied cell row by row:
+/// column, row, health (0 without any) and a kind bitmask (unit 1, enemy 2, wall 4).
+/// Then base health, score and gold, and the wave, enemies left to spawn and countdown
+fn board_hash(board: &Board, stats: &GameStats, waves: &Waves, pieces: &Query<PieceKind>) -> u64 {
+    let mut hasher = Fnv1a::new();
+
+    for coord in board.cells() {
+        let piece = match board.occupant(coord).map(|entity| pieces.get(entity)) {
+            Some(Ok(piece)) => piece,
+            _ => continue,
+        };
+
+        let (health, unit, enemy, wall) = piece;
+        let kind =
+            unit.is_some() as u32 | (enemy.is_some() as u32) << 1 | (wall.is_some() as u32) << 2;
+
+        hasher.write_u32(coord.column);
+        hasher.write_u32(coord.row);
+        hasher.write_u32(health.map_or(0, |health| health.current));
+        hasher.write_u32(kind);
+    }
+
+    for value in [
+        stats.base_health,
+        stats.score,
+        stats.gold,
+        waves.current,
+        waves.to_spawn,
+        waves.countdown,
+    ] {
+        hasher.write_u32(value);
+    }
+
+    hasher.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate::command_defender::{
+        history::{HistoryEntry, Outcome},
+        CommandHistory, GridCoord,
+    };
+
+    /// Host and joiner over loopback, the turns are as short as a frame
+    fn connected_apps() -> (App, App) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let host = thread::spawn(move || NetSession::host(&listener).unwrap());
+        let joiner = NetSession::join(address).unwrap();
+        let host = host.join().unwrap();
+
+        assert_eq!(host.side, Side::Defender);
+        assert_eq!(joiner.side, Side::Attacker);
+        assert_eq!(host.seed, joiner.seed);
+
+        (app(host), app(joiner))
+    }
+
+    fn app(session: NetSession) -> App {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(session.game_plugin(CommandDefenderPlugin {
+                headless: true,
+                history_path: None,
+                ..default()
+            }))
+            .add_plugin(NetworkPlugin {
+                turn_seconds: 0.0,
+                ..NetworkPlugin::new(session)
+            });
+
+        app
+    }
+
+    /// Updates each app until it has played `turn` turns or stopped
+    fn play_until(apps: &mut [&mut App], turn: u64) {
+        for _ in 0..10_000 {
+            let mut done = true;
+
+            for app in apps.iter_mut() {
+                let lockstep = app.world.resource::<Lockstep>();
+
+                if lockstep.turn < turn && lockstep.status == NetStatus::Playing {
+                    app.update();
+                    done = false;
+                }
+            }
+
+            if done {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        panic!("the games never reached turn {}", turn);
+    }
+
+    type HashParams = (
+        Res<'static, Board>,
+        Res<'static, GameStats>,
+        Res<'static, Waves>,
+        Query<'static, 'static, PieceKind<'static>>,
+    );
+
+    fn hash(app: &mut App) -> u64 {
+        let mut state: SystemState<HashParams> = SystemState::new(&mut app.world);
+        let (board, stats, waves, pieces) = state.get(&app.world);
+
+        board_hash(&board, &stats, &waves, &pieces)
+    }
+
+    fn has_unit(app: &App, coord: GridCoord) -> bool {
+        let board = app.world.resource::<Board>();
+
+        board
+            .occupant(coord)
+            .is_some_and(|entity| app.world.get::<Unit>(entity).is_some())
+    }
+
+    #[test]
+    fn both_ends_play_the_same_turns() {
+        let (mut host, mut joiner) = connected_apps();
+
+        host.world.send_event(LocalCommand("A3:B3".to_string()));
+        host.world.send_event(LocalCommand("A4:B4".to_string()));
+        play_until(&mut [&mut host, &mut joiner], 3);
+
+        host.world.send_event(LocalCommand("B3:C3".to_string()));
+        play_until(&mut [&mut host, &mut joiner], 6);
+
+        for app in [&host, &joiner] {
+            let lockstep = app.world.resource::<Lockstep>();
+            assert_eq!(lockstep.status, NetStatus::Playing);
+            assert_eq!(lockstep.turn, 6);
+
+            // The defender's moves ran on both boards
+            assert!(has_unit(app, GridCoord::new(2, 2)));
+            assert!(has_unit(app, GridCoord::new(1, 3)));
+        }
+
+        assert_eq!(hash(&mut host), hash(&mut joiner));
+    }
+
+    #[test]
+    fn fnv1a_matches_the_reference() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = Fnv1a::new();
+            hasher.write(bytes);
+            hasher.0
+        };
+
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn board_hash_is_stable() {
+        let mut app = App::new();
+        app.insert_resource(Board::new(4, 4, 10.0))
+            .insert_resource(GameStats {
+                base_health: 10,
+                max_base_health: 10,
+                score: 3,
+                gold: 5,
+            })
+            .insert_resource(Waves::new(5));
+
+        let unit = app.world.spawn((Unit, Health { current: 2, max: 3 })).id();
+        let wall = app.world.spawn(Wall).id();
+        let mut board = app.world.resource_mut::<Board>();
+        board.place(unit, GridCoord::new(1, 2));
+        board.place(wall, GridCoord::new(0, 3));
+
+        // Changing this breaks playing against older builds, bump `PROTOCOL_VERSION` with it
+        assert_eq!(hash(&mut app), 0x2fe6_8c3f_cb6e_c458);
+    }
+
+    #[test]
+    fn console_lines_stay_local_mid_turn() {
+        let (mut host, mut joiner) = connected_apps();
+
+        // Sent with this turn, released once the joiner's arrives
+        host.world.send_event(LocalCommand("A3:B3".to_string()));
+        host.update();
+        assert!(host.world.resource::<Lockstep>().sent.is_some());
+
+        host.world.resource_mut::<ConsoleState>().open = true;
+        host.world
+            .send_event(LocalCommand("get GameStats.gold".to_string()));
+        play_until(&mut [&mut host, &mut joiner], 3);
+
+        for app in [&host, &joiner] {
+            assert_eq!(app.world.resource::<Lockstep>().status, NetStatus::Playing);
+            assert!(has_unit(app, GridCoord::new(1, 2)));
+        }
+        assert_eq!(hash(&mut host), hash(&mut joiner));
+
+        let entries = &host.world.resource::<CommandHistory>().entries;
+        assert_eq!(
+            entries,
+            &[
+                HistoryEntry {
+                    input: "get GameStats.gold".to_string(),
+                    outcome: Outcome::Executed,
+                    output: Some("5".to_string()),
+                    by: None,
+                },
+                HistoryEntry {
+                    input: "A3:B3".to_string(),
+                    outcome: Outcome::Executed,
+                    output: None,
+                    by: None,
+                },
+            ]
+        );
+        // Never sent to the joiner
+        assert!(joiner
+            .world
+            .resource::<CommandHistory>()
+            .entries
+            .iter()
+            .all(|entry| entry.input == "A3:B3"));
+    }
+
+    #[test]
+    fn a_different_board_is_a_desync() {
+        let (mut host, mut joiner) = connected_apps();
+
+        play_until(&mut [&mut host, &mut joiner], 2);
+        assert_eq!(hash(&mut host), hash(&mut joiner));
+
+        host.world.resource_mut::<GameStats>().gold += 1;
+        play_until(&mut [&mut host, &mut joiner], 4);
+
+        for app in [&host, &joiner] {
+            assert_eq!(
+                app.world.resource::<Lockstep>().status,
+                NetStatus::Desynced(2)
+            );
+        }
+    }
+}
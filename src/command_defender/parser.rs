@@ -0,0 +1,488 @@
+use std::{fmt, str::FromStr};
+
+/// A single cell on the board, both values are zero based.
+///
+/// Displayed the way players type it, column letters then the row number, eg. `B2` or `AA12`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GridCoord {
+    pub column: u32,
+    pub row: u32,
+}
+
+impl GridCoord {
+    pub fn new(column: u32, row: u32) -> Self {
+        Self { column, row }
+    }
+
+    /// Spreadsheet style letters, `A`..`Z` then `AA`, `AB`...
+    pub fn column_label(column: u32) -> String {
+        let mut label = Vec::new();
+        let mut remaining = column as u64 + 1;
+
+        while remaining > 0 {
+            let letter = ((remaining - 1) % 26) as u8;
+            label.push((b'A' + letter) as char);
+            remaining = (remaining - 1) / 26;
+        }
+
+        label.iter().rev().collect()
+    }
+}
+
+impl fmt::Display for GridCoord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            GridCoord::column_label(self.column),
+            self.row as u64 + 1
+        )
+    }
+}
+
+impl FromStr for GridCoord {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let coord = parser.coord()?;
+        parser.end()?;
+
+        Ok(coord)
+    }
+}
+
+/// Inclusive rectangle of cells between two corners, eg. `A1-A5`.
+/// A single cell is a range that starts and ends on the same cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridRange {
+    pub start: GridCoord,
+    pub end: GridCoord,
+}
+
+impl GridRange {
+    pub fn single(coord: GridCoord) -> Self {
+        Self {
+            start: coord,
+            end: coord,
+        }
+    }
+
+    pub fn is_single(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Top left most cell, regardless of the order the corners were typed in
+    pub fn min(&self) -> GridCoord {
+        GridCoord::new(
+            self.start.column.min(self.end.column),
+            self.start.row.min(self.end.row),
+        )
+    }
+
+    pub fn max(&self) -> GridCoord {
+        GridCoord::new(
+            self.start.column.max(self.end.column),
+            self.start.row.max(self.end.row),
+        )
+    }
+
+    pub fn contains(&self, coord: GridCoord) -> bool {
+        let (min, max) = (self.min(), self.max());
+
+        (min.column..=max.column).contains(&coord.column)
+            && (min.row..=max.row).contains(&coord.row)
+    }
+
+    /// Every cell in the range, row by row
+    pub fn cells(&self) -> impl Iterator<Item = GridCoord> {
+        let (min, max) = (self.min(), self.max());
+
+        (min.row..=max.row).flat_map(move |row| {
+            (min.column..=max.column).map(move |column| GridCoord::new(column, row))
+        })
+    }
+}
+
+impl fmt::Display for GridRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_single() {
+            true => write!(f, "{}", self.start),
+            false => write!(f, "{}-{}", self.start, self.end),
+        }
+    }
+}
+
+impl FromStr for GridRange {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let range = parser.range()?;
+        parser.end()?;
+
+        Ok(range)
+    }
+}
+
+/// Move whatever is in `from` to `to`, eg. `B2:C3`.
+///
+/// With a range (`A1-A5:C3`) the cells keep their layout, the range's first corner lands on `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridCommand {
+    pub from: GridRange,
+    pub to: GridCoord,
+}
+
+impl fmt::Display for GridCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.from, self.to)
+    }
+}
+
+impl FromStr for GridCommand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+
+        if parser.peek().is_none() {
+            return Err(parser.error(ParseErrorKind::Empty));
+        }
+
+        let from = parser.range()?;
+        parser.expect(':')?;
+        let to = parser.coord()?;
+        parser.end()?;
+
+        Ok(GridCommand { from, to })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Empty,
+    /// `None` means the command ended early
+    ExpectedColumn(Option<char>),
+    ExpectedRow(Option<char>),
+    Expected(char, Option<char>),
+    /// Rows start at 1
+    ZeroRow,
+    TooLarge,
+    TrailingInput(char),
+}
+
+/// Where and why a command couldn't be parsed, `position` is the zero based character index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let found = |c: &Option<char>| match c {
+            Some(c) => format!("'{}'", c),
+            None => "the end of the command".to_string(),
+        };
+        let at = self.position + 1;
+
+        match &self.kind {
+            ParseErrorKind::Empty => write!(f, "no command entered"),
+            ParseErrorKind::ExpectedColumn(c) => {
+                write!(f, "expected a column letter at {}, found {}", at, found(c))
+            }
+            ParseErrorKind::ExpectedRow(c) => {
+                write!(f, "expected a row number at {}, found {}", at, found(c))
+            }
+            ParseErrorKind::Expected(expected, c) => {
+                write!(f, "expected '{}' at {}, found {}", expected, at, found(c))
+            }
+            ParseErrorKind::ZeroRow => write!(f, "rows start at 1, found 0 at {}", at),
+            ParseErrorKind::TooLarge => write!(f, "coordinate at {} is too large", at),
+            ParseErrorKind::TrailingInput(c) => {
+                write!(f, "unexpected '{}' at {} after the command", c, at)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Hand written recursive descent parser, small enough that a parser crate isn't worth it
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        let mut parser = Self {
+            chars: input.chars().collect(),
+            position: 0,
+        };
+
+        parser.skip_whitespace();
+        parser
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            position: self.position,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            found => Err(self.error(ParseErrorKind::Expected(expected, found))),
+        }
+    }
+
+    fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(ParseErrorKind::TrailingInput(c))),
+        }
+    }
+
+    fn range(&mut self) -> Result<GridRange, ParseError> {
+        let start = self.coord()?;
+
+        if self.peek() != Some('-') {
+            return Ok(GridRange::single(start));
+        }
+
+        self.position += 1;
+        let end = self.coord()?;
+
+        Ok(GridRange { start, end })
+    }
+
+    fn coord(&mut self) -> Result<GridCoord, ParseError> {
+        let start = self.position;
+        let column = self.column()?;
+        let row = self.row()?;
+
+        let too_large = ParseError {
+            kind: ParseErrorKind::TooLarge,
+            position: start,
+        };
+
+        Ok(GridCoord::new(
+            u32::try_from(column).map_err(|_| too_large.clone())?,
+            u32::try_from(row).map_err(|_| too_large)?,
+        ))
+    }
+
+    /// Bijective base 26, `A` is 0, `Z` is 25 and `AA` is 26
+    fn column(&mut self) -> Result<u64, ParseError> {
+        let start = self.position;
+        let mut column: u64 = 0;
+
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            let letter = (c.to_ascii_uppercase() as u8 - b'A') as u64 + 1;
+
+            column = column
+                .checked_mul(26)
+                .and_then(|column| column.checked_add(letter))
+                .ok_or(ParseError {
+                    kind: ParseErrorKind::TooLarge,
+                    position: start,
+                })?;
+
+            self.position += 1;
+        }
+
+        match self.position == start {
+            true => Err(self.error(ParseErrorKind::ExpectedColumn(self.peek()))),
+            false => Ok(column - 1),
+        }
+    }
+
+    /// Players type rows starting at 1, stored zero based
+    fn row(&mut self) -> Result<u64, ParseError> {
+        let start = self.position;
+        let mut row: u64 = 0;
+
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            row = row
+                .checked_mul(10)
+                .and_then(|row| row.checked_add(digit as u64))
+                .ok_or(ParseError {
+                    kind: ParseErrorKind::TooLarge,
+                    position: start,
+                })?;
+
+            self.position += 1;
+        }
+
+        if self.position == start {
+            return Err(self.error(ParseErrorKind::ExpectedRow(self.peek())));
+        }
+
+        match row {
+            0 => Err(ParseError {
+                kind: ParseErrorKind::ZeroRow,
+                position: start,
+            }),
+            row => Ok(row - 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_defender::board::Board;
+
+    fn coord(s: &str) -> GridCoord {
+        s.parse().unwrap()
+    }
+
+    fn error(s: &str) -> ParseError {
+        s.parse::<GridCommand>().unwrap_err()
+    }
+
+    #[test]
+    fn parses_coords() {
+        assert_eq!(coord("A1"), GridCoord::new(0, 0));
+        assert_eq!(coord("B2"), GridCoord::new(1, 1));
+        assert_eq!(coord("Z10"), GridCoord::new(25, 9));
+        assert_eq!(coord("AA12"), GridCoord::new(26, 11));
+        assert_eq!(coord("AB1"), GridCoord::new(27, 0));
+    }
+
+    #[test]
+    fn coords_round_trip_through_display() {
+        for s in ["A1", "Z26", "AA1", "AZ9", "BA100", "ZZ3"] {
+            assert_eq!(coord(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn parses_ranges() {
+        let range: GridRange = "C3-A1".parse().unwrap();
+
+        assert_eq!(range.start, GridCoord::new(2, 2));
+        assert_eq!(range.end, GridCoord::new(0, 0));
+        assert_eq!(range.min(), GridCoord::new(0, 0));
+        assert_eq!(range.max(), GridCoord::new(2, 2));
+        assert_eq!(range.cells().count(), 9);
+        assert!(range.contains(GridCoord::new(1, 2)));
+
+        let single: GridRange = "B2".parse().unwrap();
+        assert!(single.is_single());
+        assert_eq!(single.to_string(), "B2");
+    }
+
+    #[test]
+    fn parses_moves() {
+        let command: GridCommand = "B2:C3".parse().unwrap();
+        assert_eq!(command.from, GridRange::single(GridCoord::new(1, 1)));
+        assert_eq!(command.to, GridCoord::new(2, 2));
+
+        let command: GridCommand = "A1-A5:C3".parse().unwrap();
+        assert_eq!(command.from.end, GridCoord::new(0, 4));
+        assert_eq!(command.to_string(), "A1-A5:C3");
+    }
+
+    #[test]
+    fn folds_case_and_trims_whitespace() {
+        assert_eq!(coord("b2"), coord("B2"));
+        assert_eq!(coord("aA12"), coord("AA12"));
+        assert_eq!(coord("  c4\t"), GridCoord::new(2, 3));
+
+        let command: GridCommand = "  a1-a5:c3  ".parse().unwrap();
+        assert_eq!(command, "A1-A5:C3".parse().unwrap());
+    }
+
+    #[test]
+    fn whitespace_inside_a_command_is_rejected() {
+        assert_eq!(
+            error("B2 : C3").kind,
+            ParseErrorKind::Expected(':', Some(' '))
+        );
+        assert_eq!(error("B 2:C3").kind, ParseErrorKind::ExpectedRow(Some(' ')));
+    }
+
+    #[test]
+    fn off_board_coords_parse_but_are_not_on_the_board() {
+        let board = Board::new(8, 8, 32.0);
+
+        assert!(board.contains(coord("H8")));
+        assert!(!board.contains(coord("I1")));
+        assert!(!board.contains(coord("A9")));
+        assert!(!board.contains(coord("ZZ999")));
+    }
+
+    #[test]
+    fn rejects_coords_that_overflow() {
+        let err = "A99999999999".parse::<GridCoord>().unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::TooLarge);
+        assert_eq!(err.position, 0);
+
+        let err = error("B2:ZZZZZZZZZZZZZZZ1");
+        assert_eq!(err.kind, ParseErrorKind::TooLarge);
+        assert_eq!(err.position, 3);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(error("").kind, ParseErrorKind::Empty);
+        assert_eq!(error("   ").kind, ParseErrorKind::Empty);
+        assert_eq!(
+            error("2B:C3").kind,
+            ParseErrorKind::ExpectedColumn(Some('2'))
+        );
+        assert_eq!(error("B:C3").kind, ParseErrorKind::ExpectedRow(Some(':')));
+        assert_eq!(error("B0:C3").kind, ParseErrorKind::ZeroRow);
+        assert_eq!(error("B2").kind, ParseErrorKind::Expected(':', None));
+        assert_eq!(error("B2:").kind, ParseErrorKind::ExpectedColumn(None));
+        assert_eq!(
+            error("B2-:C3").kind,
+            ParseErrorKind::ExpectedColumn(Some(':'))
+        );
+        assert_eq!(error("B2:C3x").kind, ParseErrorKind::TrailingInput('x'));
+    }
+
+    #[test]
+    fn reports_the_error_column() {
+        // Positions are zero based, the message is one based like a text cursor
+        let err = error("B2:C");
+        assert_eq!(err.position, 4);
+        assert_eq!(
+            err.to_string(),
+            "expected a row number at 5, found the end of the command"
+        );
+
+        // Leading whitespace still counts towards the column
+        let err = error("  B2;C3");
+        assert_eq!(err.position, 4);
+        assert_eq!(err.to_string(), "expected ':' at 5, found ';'");
+
+        let err = error("B2:C3 D4");
+        assert_eq!(err.position, 6);
+        assert_eq!(err.to_string(), "unexpected 'D' at 7 after the command");
+
+        let err = error("B2:C0");
+        assert_eq!(err.position, 4);
+        assert_eq!(err.to_string(), "rows start at 1, found 0 at 5");
+    }
+}
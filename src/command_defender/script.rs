@@ -0,0 +1,415 @@
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::{app::AppExit, app::ScheduleRunnerSettings, ecs::schedule::ShouldRun, prelude::*};
+
+use super::{
+    game::{self, GameTickStage},
+    net::{NetSession, NetworkPlugin},
+    CommandDefenderPlugin, CommandExecuted, CommandRejected, LocalCommand, LocalSide, Systems,
+};
+
+/// Waves spawn from this seed when a headless game isn't given one, so a script plays
+/// out the same every time
+pub const SCRIPT_SEED: u64 = 0;
+
+/// Where a headless game reads its commands from
+#[derive(Debug, Clone)]
+pub enum CommandSource {
+    Stdin,
+    /// A `.cmds` file, one command per line
+    File(PathBuf),
+}
+
+/// Runs each line from `source` through the same parsing and execution as the command bar
+/// and prints every result to stdout, one tab separated line each:
+///
+/// ```text
+/// ok      B2:C3
+/// err     B2:Z9   Z9 is off the board
+/// out     <a line of output, eg. from help>
+/// ```
+///
+/// Blank lines and lines starting with `#` are skipped. Lines from a saved command history
+/// (`ok<TAB>B2:C3`) are run as their command, so a bug report's history can be replayed.
+/// `wait [ticks]` holds the next line back until that many game ticks (default 1) have run.
+/// The app exits once the source runs out and the last command has its result
+pub struct CommandScriptPlugin {
+    pub source: CommandSource,
+}
+
+impl Plugin for CommandScriptPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Script::read(self.source.clone()))
+            .init_resource::<ScriptOutput>()
+            .add_system(feed_script.label(Systems::RecordInput))
+            .add_system(print_results.after(Systems::Handle))
+            .add_system_to_stage(GameTickStage, count_ticks.after(game::check_outcome));
+    }
+}
+
+/// Plays Command Defender without a window, eg. `cargo run -- --headless level.cmds`.
+/// Waves spawn from `seed`, or `SCRIPT_SEED` without one, and the game only ticks when the
+/// script says `wait`. With a `session` the script plays its side against the other player,
+/// the host's seed and the turns are used instead
+pub fn run_headless(source: CommandSource, session: Option<NetSession>, seed: Option<u64>) {
+    headless_app(source, session, seed).run();
+}
+
+fn headless_app(source: CommandSource, session: Option<NetSession>, seed: Option<u64>) -> App {
+    let mut app = App::new();
+
+    let game = CommandDefenderPlugin {
+        headless: true,
+        history_path: None,
+        wave_seed: Some(seed.unwrap_or(SCRIPT_SEED)),
+        ..default()
+    };
+
+    app.insert_resource(ScheduleRunnerSettings::run_loop(Duration::from_secs_f64(
+        1.0 / 60.0,
+    )))
+    .add_plugins(MinimalPlugins);
+
+    match session {
+        Some(session) => app
+            .add_plugin(session.game_plugin(game))
+            .add_plugin(NetworkPlugin::new(session)),
+        // Ticks follow the script instead of the clock
+        None => app
+            .add_plugin(game)
+            .stage(GameTickStage, |stage: &mut SystemStage| {
+                stage.set_run_criteria(script_waiting)
+            }),
+    };
+
+    app.add_plugin(CommandScriptPlugin { source });
+    app
+}
+
+#[derive(Resource)]
+struct Script {
+    /// Filled from another thread so reading stdin doesn't block the app
+    lines: Mutex<Receiver<String>>,
+    /// A command was sent and its result hasn't come back yet
+    waiting: bool,
+    /// Game ticks still to run for a `wait`
+    ticks: u32,
+}
+
+/// Where the result lines go, collected instead of printed when testing
+#[derive(Resource, Default)]
+enum ScriptOutput {
+    #[default]
+    Stdout,
+    #[cfg(test)]
+    Collect(Vec<String>),
+}
+
+impl ScriptOutput {
+    fn write(&mut self, line: String) {
+        match self {
+            ScriptOutput::Stdout => println!("{}", line),
+            #[cfg(test)]
+            ScriptOutput::Collect(lines) => lines.push(line),
+        }
+    }
+}
+
+impl Script {
+    fn read(source: CommandSource) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        match source {
+            CommandSource::Stdin => {
+                thread::spawn(move || {
+                    for line in io::stdin().lock().lines().map_while(Result::ok) {
+                        if sender.send(line).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            // Dropping the sender after the last line ends the script
+            CommandSource::File(path) => match fs::read_to_string(&path) {
+                Ok(contents) => contents.lines().for_each(|line| {
+                    let _ = sender.send(line.to_string());
+                }),
+                Err(error) => eprintln!("Couldn't read {:?}: {}", path, error),
+            },
+        }
+
+        Self {
+            lines: Mutex::new(receiver),
+            waiting: false,
+            ticks: 0,
+        }
+    }
+}
+
+/// The command on a script line, `None` for blanks and comments
+fn script_command(line: &str) -> Option<&str> {
+    let line = line.trim();
+
+    // Saved history, `ok<TAB>input` or `err<TAB>input<TAB>reason`
+    let line = match line.split('\t').collect::<Vec<_>>().as_slice() {
+        ["ok", input] | ["err", input, _] => input.trim(),
+        _ => line,
+    };
+
+    match line.is_empty() || line.starts_with('#') {
+        true => None,
+        false => Some(line),
+    }
+}
+
+/// Ticks a `wait` line asks for, `None` when it isn't one
+fn wait_ticks(command: &str) -> Option<Result<u32, String>> {
+    let mut words = command.split_whitespace();
+
+    if !words.next()?.eq_ignore_ascii_case("wait") {
+        return None;
+    }
+
+    Some(match (words.next(), words.next()) {
+        (None, _) => Ok(1),
+        (Some(ticks), None) => ticks
+            .parse()
+            .map_err(|_| "expected a number of ticks, usage: wait [ticks]".to_string()),
+        _ => Err("wait takes 1 argument(s), usage: wait [ticks]".to_string()),
+    })
+}
+
+/// Sends the next command once the previous one has its result and any `wait` is over, so
+/// each one sees the board the last one left behind
+fn feed_script(
+    mut script: ResMut<Script>,
+    mut output: ResMut<ScriptOutput>,
+    mut ev_local: EventWriter<LocalCommand>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    if script.waiting || script.ticks > 0 {
+        return;
+    }
+
+    loop {
+        let next = script.lines.lock().unwrap().try_recv();
+
+        match next {
+            Ok(line) => {
+                let command = match script_command(&line) {
+                    Some(command) => command,
+                    None => continue,
+                };
+
+                match wait_ticks(command) {
+                    Some(Ok(ticks)) => {
+                        output.write(format!("ok\t{}", command));
+                        script.ticks = ticks;
+                    }
+                    Some(Err(reason)) => output.write(format!("err\t{}\t{}", command, reason)),
+                    None => {
+                        ev_local.send(LocalCommand(command.to_string()));
+                        script.waiting = true;
+                    }
+                }
+
+                if script.waiting || script.ticks > 0 {
+                    return;
+                }
+            }
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                ev_exit.send(AppExit);
+                return;
+            }
+        }
+    }
+}
+
+/// Run criteria for the game tick without a network session, it only ticks for a `wait`
+fn script_waiting(script: Res<Script>) -> ShouldRun {
+    match script.ticks > 0 {
+        true => ShouldRun::Yes,
+        false => ShouldRun::No,
+    }
+}
+
+fn count_ticks(mut script: ResMut<Script>) {
+    script.ticks = script.ticks.saturating_sub(1);
+}
+
+/// Only the script's own side is printed, the other side's moves show up on the board
+fn print_results(
+    mut script: ResMut<Script>,
+    mut output: ResMut<ScriptOutput>,
+    local: Res<LocalSide>,
+    mut ev_executed: EventReader<CommandExecuted>,
+    mut ev_rejected: EventReader<CommandRejected>,
+) {
+    for ev in ev_executed.iter().filter(|ev| ev.side == local.0) {
+        output.write(format!("ok\t{}", ev.input));
+
+        for line in ev.output.iter().flat_map(|output| output.lines()) {
+            output.write(format!("out\t{}", line));
+        }
+
+        script.waiting = false;
+    }
+
+    for ev in ev_rejected.iter().filter(|ev| ev.side == local.0) {
+        output.write(format!("err\t{}\t{}", ev.input, ev.reason));
+        script.waiting = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+    use crate::command_defender::{board::BoardPosition, game::Enemy, GridCoord};
+
+    #[test]
+    fn skips_blank_lines_and_comments() {
+        assert_eq!(script_command(""), None);
+        assert_eq!(script_command("   \t "), None);
+        assert_eq!(script_command("# a comment"), None);
+        assert_eq!(script_command("  # indented"), None);
+        assert_eq!(script_command("  B2:C3  "), Some("B2:C3"));
+    }
+
+    #[test]
+    fn runs_saved_history_lines_as_their_command() {
+        assert_eq!(script_command("ok\tB2:C3"), Some("B2:C3"));
+        assert_eq!(
+            script_command("err\tB2:Z9\tZ9 is off the board"),
+            Some("B2:Z9")
+        );
+        assert_eq!(
+            script_command("ok\t  build A1 wall "),
+            Some("build A1 wall")
+        );
+        assert_eq!(script_command("ok\t# commented out"), None);
+
+        // Anything else with tabs is left alone for the parser to reject
+        assert_eq!(script_command("nope\tB2:C3"), Some("nope\tB2:C3"));
+        assert_eq!(script_command("ok\tB2:C3\textra"), Some("ok\tB2:C3\textra"));
+    }
+
+    #[test]
+    fn reads_wait_lines() {
+        assert_eq!(wait_ticks("wait"), Some(Ok(1)));
+        assert_eq!(wait_ticks("WAIT 12"), Some(Ok(12)));
+        assert!(matches!(wait_ticks("wait soon"), Some(Err(_))));
+        assert!(matches!(wait_ticks("wait 1 2"), Some(Err(_))));
+        assert_eq!(wait_ticks("waiting"), None);
+        assert_eq!(wait_ticks("B2:C3"), None);
+    }
+
+    #[derive(Resource, Default)]
+    struct Ticks(u32);
+
+    /// Plays a fixture to the end, returns the app and what it printed
+    fn play(fixture: &str, seed: Option<u64>) -> (App, Vec<String>) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(fixture);
+        let mut app = headless_app(CommandSource::File(path), None, seed);
+        app.insert_resource(ScriptOutput::Collect(vec![]))
+            .init_resource::<Ticks>()
+            .add_system_to_stage(GameTickStage, |mut ticks: ResMut<Ticks>| ticks.0 += 1);
+
+        let mut ev_exit = ManualEventReader::<AppExit>::default();
+        let exited = (0..1000).any(|_| {
+            app.update();
+            ev_exit
+                .iter(app.world.resource::<Events<AppExit>>())
+                .next()
+                .is_some()
+        });
+        assert!(exited, "the script never finished");
+
+        let lines = match app.world.resource::<ScriptOutput>() {
+            ScriptOutput::Collect(lines) => lines.clone(),
+            ScriptOutput::Stdout => unreachable!(),
+        };
+
+        (app, lines)
+    }
+
+    fn enemies(app: &mut App) -> Vec<GridCoord> {
+        let mut cells: Vec<GridCoord> = app
+            .world
+            .query_filtered::<&BoardPosition, With<Enemy>>()
+            .iter(&app.world)
+            .map(|position| position.0)
+            .collect();
+        cells.sort_by_key(|coord| (coord.column, coord.row));
+        cells
+    }
+
+    #[test]
+    fn plays_a_cmds_file_headless() {
+        let (app, lines) = play("script.cmds", None);
+
+        // No `wait`, so no time passes
+        assert_eq!(app.world.resource::<Ticks>().0, 0);
+        assert_eq!(
+            lines,
+            [
+                "ok\tA3:B3",
+                "ok\tA4:B4",
+                "err\tB3:Z9\tZ9 is off the board",
+                "err\tB4:B3\tB3 is already occupied",
+                "err\tattack\tmissing <target>, usage: attack <target>",
+                "err\thelp me\thelp takes 0 argument(s), usage: help",
+                "ok\tbuild A1 wall",
+            ]
+        );
+    }
+
+    #[test]
+    fn ticks_only_for_wait() {
+        let (mut app, lines) = play("waves.cmds", None);
+
+        assert_eq!(app.world.resource::<Ticks>().0, 9);
+        assert_eq!(
+            lines,
+            [
+                "ok\twait 6",
+                "ok\tbuild A1 wall",
+                "ok\twait",
+                "err\twait soon\texpected a number of ticks, usage: wait [ticks]",
+                "ok\twait 2",
+            ]
+        );
+        assert!(!enemies(&mut app).is_empty());
+    }
+
+    #[test]
+    fn plays_the_same_every_time() {
+        let (mut first, first_lines) = play("waves.cmds", None);
+        let (mut second, second_lines) = play("waves.cmds", None);
+
+        assert_eq!(first_lines, second_lines);
+        assert_eq!(enemies(&mut first), enemies(&mut second));
+
+        let seeded: Vec<_> = (1..6)
+            .map(|seed| enemies(&mut play("waves.cmds", Some(seed)).0))
+            .collect();
+        assert!(
+            seeded.iter().any(|cells| *cells != enemies(&mut first)),
+            "the seed makes no difference"
+        );
+    }
+}
@@ -0,0 +1,576 @@
+use bevy::prelude::*;
+
+use super::{
+    board::{self, Board, BoardPosition},
+    game::{self, GameState, GameStats},
+    input::CommandFocus,
+    parser::GridCoord,
+    verbs::{FromVerbArgs, VerbArgs, VerbCall},
+    CommandExecuted, CommandRejected, LocalCommand,
+};
+
+/// A unit going from one cell to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub entity: Entity,
+    pub from: GridCoord,
+    pub to: GridCoord,
+}
+
+/// Something a command did to the board that can be taken back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardAction {
+    /// Every unit a move command moved
+    Move(Vec<Step>),
+    /// `build`, undoing it refunds the cost
+    Build {
+        entity: Entity,
+        cell: GridCoord,
+        kind: &'static str,
+        cost: u32,
+    },
+}
+
+impl BoardAction {
+    fn replace_entity(&mut self, old: Entity, new: Entity) {
+        match self {
+            BoardAction::Move(steps) => steps
+                .iter_mut()
+                .filter(|step| step.entity == old)
+                .for_each(|step| step.entity = new),
+            BoardAction::Build { entity, .. } if *entity == old => *entity = new,
+            BoardAction::Build { .. } => {}
+        }
+    }
+}
+
+/// Executed board actions for `undo`, and undone ones for `redo`
+#[derive(Resource, Debug, Default)]
+pub struct UndoHistory {
+    done: Vec<BoardAction>,
+    undone: Vec<BoardAction>,
+    /// Actions kept to undo, the oldest are dropped first
+    pub limit: usize,
+    /// Off in competitive games, nothing is recorded then
+    pub enabled: bool,
+}
+
+impl UndoHistory {
+    pub fn new(limit: usize, enabled: bool) -> Self {
+        Self {
+            limit,
+            enabled,
+            ..default()
+        }
+    }
+
+    /// A new action can't be redone past, so it clears the redo stack
+    pub fn record(&mut self, action: BoardAction) {
+        if !self.enabled {
+            return;
+        }
+
+        self.undone.clear();
+        self.done.push(action);
+
+        if self.done.len() > self.limit {
+            let excess = self.done.len() - self.limit;
+            self.done.drain(..excess);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Redoing a build spawns a new entity, older actions on the same piece follow it
+    fn replace_entity(&mut self, old: Entity, new: Entity) {
+        for action in self.done.iter_mut().chain(self.undone.iter_mut()) {
+            action.replace_entity(old, new);
+        }
+    }
+}
+
+/// `undo`
+pub(super) struct Undo;
+
+impl FromVerbArgs for Undo {
+    fn from_args(_: &VerbArgs) -> Option<Self> {
+        Some(Undo)
+    }
+}
+
+/// `redo`
+pub(super) struct Redo;
+
+impl FromVerbArgs for Redo {
+    fn from_args(_: &VerbArgs) -> Option<Self> {
+        Some(Redo)
+    }
+}
+
+/// Ctrl+Z and Ctrl+Y (or Ctrl+Shift+Z) type `undo` and `redo` so they show up in the log
+pub(super) fn undo_keys(
+    keys: Res<Input<KeyCode>>,
+    focus: Res<CommandFocus>,
+    mut ev_local: EventWriter<LocalCommand>,
+) {
+    if !focus.focused {
+        return;
+    }
+
+    let ctrl = keys.any_pressed([
+        KeyCode::LControl,
+        KeyCode::RControl,
+        KeyCode::LWin,
+        KeyCode::RWin,
+    ]);
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+
+    if !ctrl {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Z) && !shift {
+        ev_local.send(LocalCommand("undo".to_string()));
+    } else if keys.just_pressed(KeyCode::Y) || keys.just_pressed(KeyCode::Z) {
+        ev_local.send(LocalCommand("redo".to_string()));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn undo_redo(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    mut board: ResMut<Board>,
+    mut stats: ResMut<GameStats>,
+    mut history: ResMut<UndoHistory>,
+    mut ev_undo: EventReader<VerbCall<Undo>>,
+    mut ev_redo: EventReader<VerbCall<Redo>>,
+    mut pieces: Query<&mut BoardPosition>,
+    mut ev_executed: EventWriter<CommandExecuted>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    for ev in ev_undo.iter() {
+        if let Some(rejected) = ev.defender_only() {
+            ev_rejected.send(rejected);
+            continue;
+        }
+
+        if let Some(reason) = unavailable(&history, &state) {
+            ev_rejected.send(ev.rejected(reason));
+            continue;
+        }
+
+        let action = match history.done.pop() {
+            Some(action) => action,
+            None => {
+                ev_rejected.send(ev.rejected("nothing to undo"));
+                continue;
+            }
+        };
+
+        match &action {
+            BoardAction::Move(steps) => {
+                let back: Vec<Step> = steps
+                    .iter()
+                    .map(|step| Step {
+                        entity: step.entity,
+                        from: step.to,
+                        to: step.from,
+                    })
+                    .collect();
+
+                if let Err(reason) = check_steps(&board, &back) {
+                    ev_rejected.send(ev.rejected(format!("can't undo, {}", reason)));
+                    history.done.push(action);
+                    continue;
+                }
+
+                board::move_pieces(&mut board, &back, &mut pieces);
+            }
+            BoardAction::Build {
+                entity, cell, cost, ..
+            } => {
+                if board.position(*entity) != Some(*cell) {
+                    ev_rejected.send(ev.rejected(format!("can't undo, {} is gone", cell)));
+                    history.done.push(action);
+                    continue;
+                }
+
+                board.vacate(*entity);
+                commands.entity(*entity).despawn();
+                stats.gold += cost;
+            }
+        }
+
+        history.undone.push(action);
+        ev_executed.send(ev.executed());
+    }
+
+    for ev in ev_redo.iter() {
+        if let Some(rejected) = ev.defender_only() {
+            ev_rejected.send(rejected);
+            continue;
+        }
+
+        if let Some(reason) = unavailable(&history, &state) {
+            ev_rejected.send(ev.rejected(reason));
+            continue;
+        }
+
+        let mut action = match history.undone.pop() {
+            Some(action) => action,
+            None => {
+                ev_rejected.send(ev.rejected("nothing to redo"));
+                continue;
+            }
+        };
+
+        match &mut action {
+            BoardAction::Move(steps) => {
+                if let Err(reason) = check_steps(&board, steps) {
+                    ev_rejected.send(ev.rejected(format!("can't redo, {}", reason)));
+                    history.undone.push(action);
+                    continue;
+                }
+
+                board::move_pieces(&mut board, steps, &mut pieces);
+            }
+            BoardAction::Build {
+                entity,
+                cell,
+                kind,
+                cost,
+            } => {
+                let spawned = if board.occupant(*cell).is_some() {
+                    Err(format!("can't redo, {} is occupied", cell))
+                } else if stats.gold < *cost {
+                    Err(format!("can't redo, a {} costs {} gold", kind, cost))
+                } else {
+                    game::spawn_building(&mut commands, &mut board, *cell, kind)
+                        .ok_or_else(|| format!("can't redo, {} is off the board", cell))
+                };
+
+                let new = match spawned {
+                    Ok(new) => new,
+                    Err(reason) => {
+                        ev_rejected.send(ev.rejected(reason));
+                        history.undone.push(action);
+                        continue;
+                    }
+                };
+
+                stats.gold -= *cost;
+                let old = std::mem::replace(entity, new);
+                history.replace_entity(old, new);
+            }
+        }
+
+        history.done.push(action);
+        ev_executed.send(ev.executed());
+    }
+}
+
+fn unavailable(history: &UndoHistory, state: &State<GameState>) -> Option<&'static str> {
+    if !history.enabled {
+        Some("undo is turned off in competitive games")
+    } else if !game::is_playing(state) {
+        Some("the game is over, type restart")
+    } else {
+        None
+    }
+}
+
+/// Every unit is still where the step starts and nothing outside the steps is in the way
+fn check_steps(board: &Board, steps: &[Step]) -> Result<(), String> {
+    for step in steps {
+        if board.position(step.entity) != Some(step.from) {
+            return Err(format!("the unit at {} has moved since", step.from));
+        }
+    }
+
+    for step in steps {
+        let is_blocked = board
+            .occupant(step.to)
+            .is_some_and(|other| !steps.iter().any(|s| s.entity == other));
+
+        if is_blocked {
+            return Err(format!("{} is occupied", step.to));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_defender::{Command, RejectReason, Side};
+
+    fn coord(s: &str) -> GridCoord {
+        s.parse().unwrap()
+    }
+
+    fn step(entity: Entity, from: &str, to: &str) -> Step {
+        Step {
+            entity,
+            from: coord(from),
+            to: coord(to),
+        }
+    }
+
+    fn build(entity: Entity, cell: &str) -> BoardAction {
+        BoardAction::Build {
+            entity,
+            cell: coord(cell),
+            kind: "wall",
+            cost: 5,
+        }
+    }
+
+    #[test]
+    fn steps_need_their_units_and_free_cells() {
+        let (a, b, c) = (
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+        );
+        let mut board = Board::new(4, 4, 10.0);
+        board.place(a, coord("A1"));
+        board.place(b, coord("A2"));
+        board.place(c, coord("C1"));
+
+        assert_eq!(check_steps(&board, &[step(a, "A1", "B1")]), Ok(()));
+        // Into a cell another step is leaving
+        assert_eq!(
+            check_steps(&board, &[step(a, "A1", "A2"), step(b, "A2", "A3")]),
+            Ok(())
+        );
+        assert_eq!(
+            check_steps(&board, &[step(a, "A1", "C1")]),
+            Err("C1 is occupied".to_string())
+        );
+        assert_eq!(
+            check_steps(&board, &[step(a, "B1", "B2")]),
+            Err("the unit at B1 has moved since".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_the_newest_actions() {
+        let mut history = UndoHistory::new(2, true);
+
+        for i in 0..4 {
+            history.record(build(Entity::from_raw(i), "A1"));
+        }
+        assert_eq!(
+            history.done,
+            [
+                build(Entity::from_raw(2), "A1"),
+                build(Entity::from_raw(3), "A1")
+            ]
+        );
+
+        // Nothing is kept while turned off
+        let mut history = UndoHistory::new(2, false);
+        history.record(build(Entity::from_raw(0), "A1"));
+        assert!(history.done.is_empty());
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut history = UndoHistory::new(5, true);
+        history.undone.push(build(Entity::from_raw(0), "A1"));
+
+        history.record(build(Entity::from_raw(1), "B1"));
+        assert!(history.undone.is_empty());
+    }
+
+    #[test]
+    fn replaced_entities_are_followed() {
+        let (old, new, other) = (
+            Entity::from_raw(0),
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+        );
+        let mut history = UndoHistory::new(5, true);
+        history.record(BoardAction::Move(vec![
+            step(old, "A1", "B1"),
+            step(other, "A2", "B2"),
+        ]));
+        history.record(build(other, "C1"));
+        history.undone.push(build(old, "A1"));
+
+        history.replace_entity(old, new);
+
+        assert_eq!(
+            history.done,
+            [
+                BoardAction::Move(vec![step(new, "A1", "B1"), step(other, "A2", "B2")]),
+                build(other, "C1"),
+            ]
+        );
+        assert_eq!(history.undone, [build(new, "A1")]);
+    }
+
+    fn app(gold: u32) -> App {
+        let mut app = App::new();
+        app.insert_resource(State::new(GameState::Playing))
+            .insert_resource(Board::new(4, 4, 10.0))
+            .insert_resource(GameStats { gold, ..default() })
+            .insert_resource(UndoHistory::new(10, true))
+            .add_event::<VerbCall<Undo>>()
+            .add_event::<VerbCall<Redo>>()
+            .add_event::<CommandExecuted>()
+            .add_event::<CommandRejected>()
+            .add_system(undo_redo);
+        app
+    }
+
+    /// A wall in `cell` that was built for 5 gold
+    fn built(app: &mut App, cell: &str) -> Entity {
+        let wall = app.world.spawn(BoardPosition(coord(cell))).id();
+        app.world.resource_mut::<Board>().place(wall, coord(cell));
+        app.world
+            .resource_mut::<UndoHistory>()
+            .record(build(wall, cell));
+        wall
+    }
+
+    fn call<T: Send + Sync + 'static>(
+        app: &mut App,
+        verb: &'static str,
+        args: T,
+    ) -> Result<(), String> {
+        app.world.send_event(VerbCall {
+            input: verb.to_string(),
+            side: Side::Defender,
+            command: Command::Verb(VerbArgs {
+                verb,
+                values: vec![],
+            }),
+            args,
+        });
+        app.update();
+
+        let executed = app
+            .world
+            .resource_mut::<Events<CommandExecuted>>()
+            .drain()
+            .count();
+        let rejected: Vec<_> = app
+            .world
+            .resource_mut::<Events<CommandRejected>>()
+            .drain()
+            .collect();
+
+        match (executed, rejected.as_slice()) {
+            (1, []) => Ok(()),
+            (0, [rejected]) => match &rejected.reason {
+                RejectReason::Verb(reason) => Err(reason.clone()),
+                reason => panic!("unexpected {:?}", reason),
+            },
+            _ => panic!("{} executed and {:?} rejected", executed, rejected),
+        }
+    }
+
+    fn gold(app: &App) -> u32 {
+        app.world.resource::<GameStats>().gold
+    }
+
+    #[test]
+    fn undo_refunds_a_build_and_redo_charges_again() {
+        let mut app = app(10);
+        let wall = built(&mut app, "B2");
+
+        assert_eq!(call(&mut app, "undo", Undo), Ok(()));
+        assert_eq!(gold(&app), 15);
+        assert!(app.world.get_entity(wall).is_none());
+        assert_eq!(app.world.resource::<Board>().occupant(coord("B2")), None);
+
+        assert_eq!(call(&mut app, "redo", Redo), Ok(()));
+        assert_eq!(gold(&app), 10);
+
+        // A new wall, and undo takes that one back
+        let rebuilt = app.world.resource::<Board>().occupant(coord("B2")).unwrap();
+        assert_ne!(rebuilt, wall);
+        assert_eq!(
+            app.world.resource::<UndoHistory>().done,
+            [build(rebuilt, "B2")]
+        );
+
+        assert_eq!(call(&mut app, "undo", Undo), Ok(()));
+        assert_eq!(gold(&app), 15);
+        assert!(app.world.get_entity(rebuilt).is_none());
+
+        assert_eq!(
+            call(&mut app, "undo", Undo),
+            Err("nothing to undo".to_string())
+        );
+    }
+
+    #[test]
+    fn redo_needs_the_gold() {
+        let mut app = app(0);
+        built(&mut app, "B2");
+
+        call(&mut app, "undo", Undo).unwrap();
+        app.world.resource_mut::<GameStats>().gold = 4;
+
+        assert_eq!(
+            call(&mut app, "redo", Redo),
+            Err("can't redo, a wall costs 5 gold".to_string())
+        );
+        assert_eq!(gold(&app), 4);
+    }
+
+    #[test]
+    fn redo_is_blocked_by_an_occupied_cell() {
+        let mut app = app(0);
+        built(&mut app, "B2");
+        call(&mut app, "undo", Undo).unwrap();
+
+        // Something walked in without going through the history
+        let enemy = app.world.spawn(BoardPosition(coord("B2"))).id();
+        app.world.resource_mut::<Board>().place(enemy, coord("B2"));
+
+        assert_eq!(
+            call(&mut app, "redo", Redo),
+            Err("can't redo, B2 is occupied".to_string())
+        );
+        assert_eq!(gold(&app), 5);
+
+        // Still there to redo once the cell is free
+        app.world.resource_mut::<Board>().vacate(enemy);
+        assert_eq!(call(&mut app, "redo", Redo), Ok(()));
+        assert_eq!(gold(&app), 0);
+    }
+
+    #[test]
+    fn moves_undo_and_redo() {
+        let mut app = app(0);
+        let unit = app.world.spawn(BoardPosition(coord("A1"))).id();
+        app.world.resource_mut::<Board>().place(unit, coord("A1"));
+        app.world
+            .resource_mut::<UndoHistory>()
+            .record(BoardAction::Move(vec![step(unit, "B1", "A1")]));
+
+        assert_eq!(call(&mut app, "undo", Undo), Ok(()));
+        assert_eq!(
+            app.world.resource::<Board>().position(unit),
+            Some(coord("B1"))
+        );
+
+        // Redo is blocked while something sits where the unit went
+        let blocker = app.world.spawn(BoardPosition(coord("A1"))).id();
+        app.world
+            .resource_mut::<Board>()
+            .place(blocker, coord("A1"));
+        assert_eq!(
+            call(&mut app, "redo", Redo),
+            Err("can't redo, A1 is occupied".to_string())
+        );
+    }
+}
@@ -0,0 +1,581 @@
+use std::{fmt, marker::PhantomData};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{
+    parser::{GridCoord, GridRange},
+    Command, CommandExecuted, CommandIssued, CommandRejected, RejectReason, Side, Systems,
+};
+
+/// What an argument has to look like
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// A single cell, eg. `B2`
+    Coord,
+    /// A cell or a range of cells, eg. `A1-A4`
+    Range,
+    /// One of a fixed set of words, matched case insensitively
+    OneOf(&'static [&'static str]),
+    Number,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub optional: bool,
+}
+
+impl fmt::Display for ArgSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.kind {
+            ArgKind::OneOf(options) => options.join("|"),
+            _ => self.name.to_string(),
+        };
+
+        match self.optional {
+            true => write!(f, "[{}]", name),
+            false => write!(f, "<{}>", name),
+        }
+    }
+}
+
+/// A command word and the arguments it takes, eg. `build <cell> <tower|wall>`
+#[derive(Debug, Clone)]
+pub struct VerbSpec {
+    pub name: &'static str,
+    /// One line shown by `help`
+    pub help: &'static str,
+    pub args: Vec<ArgSpec>,
+}
+
+impl VerbSpec {
+    pub fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            args: vec![],
+        }
+    }
+
+    pub fn arg(mut self, name: &'static str, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec {
+            name,
+            kind,
+            optional: false,
+        });
+        self
+    }
+
+    /// Optional arguments have to come after the required ones
+    pub fn optional(mut self, name: &'static str, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec {
+            name,
+            kind,
+            optional: true,
+        });
+        self
+    }
+
+    pub fn usage(&self) -> String {
+        std::iter::once(self.name.to_string())
+            .chain(self.args.iter().map(|arg| arg.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Checks the words after the verb against the arguments
+    pub fn parse(&self, words: &[&str]) -> Result<VerbArgs, ArgError> {
+        let error = |kind| ArgError {
+            verb: self.name,
+            usage: self.usage(),
+            kind,
+        };
+
+        if words.len() > self.args.len() {
+            return Err(error(ArgErrorKind::TooMany(self.args.len())));
+        }
+
+        let mut values = vec![];
+
+        for (i, spec) in self.args.iter().enumerate() {
+            let word = match words.get(i) {
+                Some(word) => word,
+                None if spec.optional => {
+                    values.push(None);
+                    continue;
+                }
+                None => return Err(error(ArgErrorKind::Missing(spec.name))),
+            };
+
+            let value = match spec.kind {
+                ArgKind::Coord => word.parse().map(ArgValue::Coord).map_err(|e| e.to_string()),
+                ArgKind::Range => word.parse().map(ArgValue::Range).map_err(|e| e.to_string()),
+                ArgKind::OneOf(options) => options
+                    .iter()
+                    .find(|option| option.eq_ignore_ascii_case(word))
+                    .map(|option| ArgValue::Word(option))
+                    .ok_or_else(|| format!("expected one of {}", options.join(", "))),
+                ArgKind::Number => word
+                    .parse()
+                    .map(ArgValue::Number)
+                    .map_err(|_| "expected a number".to_string()),
+            };
+
+            match value {
+                Ok(value) => values.push(Some(value)),
+                Err(reason) => {
+                    return Err(error(ArgErrorKind::Invalid {
+                        arg: spec.name,
+                        reason,
+                    }))
+                }
+            }
+        }
+
+        Ok(VerbArgs {
+            verb: self.name,
+            values,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgValue {
+    Coord(GridCoord),
+    Range(GridRange),
+    Word(&'static str),
+    Number(i64),
+}
+
+/// Arguments of a verb call that passed its `VerbSpec`, in the same order as the spec.
+/// Optional arguments that weren't given are `None`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerbArgs {
+    pub verb: &'static str,
+    pub values: Vec<Option<ArgValue>>,
+}
+
+impl VerbArgs {
+    pub fn coord(&self, index: usize) -> Option<GridCoord> {
+        match self.values.get(index)? {
+            Some(ArgValue::Coord(coord)) => Some(*coord),
+            _ => None,
+        }
+    }
+
+    /// Also accepts a single `Coord`
+    pub fn range(&self, index: usize) -> Option<GridRange> {
+        match self.values.get(index)? {
+            Some(ArgValue::Range(range)) => Some(*range),
+            Some(ArgValue::Coord(coord)) => Some(GridRange::single(*coord)),
+            _ => None,
+        }
+    }
+
+    pub fn word(&self, index: usize) -> Option<&'static str> {
+        match self.values.get(index)? {
+            Some(ArgValue::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, index: usize) -> Option<i64> {
+        match self.values.get(index)? {
+            Some(ArgValue::Number(number)) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for VerbArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.verb)?;
+
+        for value in self.values.iter().flatten() {
+            match value {
+                ArgValue::Coord(coord) => write!(f, " {}", coord)?,
+                ArgValue::Range(range) => write!(f, " {}", range)?,
+                ArgValue::Word(word) => write!(f, " {}", word)?,
+                ArgValue::Number(number) => write!(f, " {}", number)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Why the words after a verb didn't match its arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgError {
+    pub verb: &'static str,
+    pub usage: String,
+    pub kind: ArgErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgErrorKind {
+    Missing(&'static str),
+    Invalid {
+        arg: &'static str,
+        reason: String,
+    },
+    /// Only this many arguments are taken
+    TooMany(usize),
+    /// Passed the `VerbSpec` but its `FromVerbArgs` couldn't read them, the two disagree
+    Mismatch,
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ArgErrorKind::Missing(arg) => write!(f, "missing <{}>", arg)?,
+            ArgErrorKind::Invalid { arg, reason } => write!(f, "<{}>: {}", arg, reason)?,
+            ArgErrorKind::TooMany(count) => write!(f, "{} takes {} argument(s)", self.verb, count)?,
+            ArgErrorKind::Mismatch => write!(f, "{} couldn't read its arguments", self.verb)?,
+        }
+
+        write!(f, ", usage: {}", self.usage)
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+/// Every verb that can be typed, filled through `AddVerb::add_verb`
+#[derive(Resource, Debug, Default)]
+pub struct VerbRegistry {
+    verbs: HashMap<&'static str, VerbSpec>,
+}
+
+impl VerbRegistry {
+    /// Verb names are matched case insensitively
+    pub fn get(&self, name: &str) -> Option<&VerbSpec> {
+        self.verbs.get(name.to_ascii_lowercase().as_str())
+    }
+
+    /// All verbs, sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = &VerbSpec> {
+        let mut verbs: Vec<&VerbSpec> = self.verbs.values().collect();
+        verbs.sort_by_key(|verb| verb.name);
+        verbs.into_iter()
+    }
+
+    /// Only the spec, use `AddVerb::add_verb` to also route calls to a handler
+    pub(super) fn insert(&mut self, spec: VerbSpec) {
+        self.verbs.insert(spec.name, spec);
+    }
+}
+
+/// Builds a verb's event from its checked arguments
+pub trait FromVerbArgs: Send + Sync + 'static + Sized {
+    /// Only called with arguments that passed the verb's `VerbSpec`, so `None` means the
+    /// spec and this don't match up
+    fn from_args(args: &VerbArgs) -> Option<Self>;
+}
+
+/// Sent to the verb's handler. The handler decides if the command worked and should send
+/// `executed` or `rejected` so it shows up in the history
+#[derive(Debug, Clone)]
+pub struct VerbCall<T> {
+    pub input: String,
+    pub side: Side,
+    pub command: Command,
+    pub args: T,
+}
+
+impl<T> VerbCall<T> {
+    pub fn executed(&self) -> CommandExecuted {
+        CommandExecuted {
+            input: self.input.clone(),
+            side: self.side,
+            command: self.command.clone(),
+            output: None,
+        }
+    }
+
+    pub fn executed_with(&self, output: impl Into<String>) -> CommandExecuted {
+        CommandExecuted {
+            output: Some(output.into()),
+            ..self.executed()
+        }
+    }
+
+    pub fn rejected(&self, reason: impl Into<String>) -> CommandRejected {
+        CommandRejected {
+            input: self.input.clone(),
+            side: self.side,
+            reason: RejectReason::Verb(reason.into()),
+        }
+    }
+
+    /// Rejection for verbs only the defender can use, `None` when it is the defender
+    pub fn defender_only(&self) -> Option<CommandRejected> {
+        match self.side {
+            Side::Defender => None,
+            side => Some(self.rejected(format!("the {} can't {}", side, self.command))),
+        }
+    }
+}
+
+pub trait AddVerb {
+    /// Registers a verb, each call of it is sent as a `VerbCall<T>` event for its handler.
+    /// Names should be lowercase, they are typed in any case
+    fn add_verb<T: FromVerbArgs>(&mut self, spec: VerbSpec) -> &mut Self;
+}
+
+impl AddVerb for App {
+    fn add_verb<T: FromVerbArgs>(&mut self, spec: VerbSpec) -> &mut Self {
+        let name = spec.name;
+
+        self.init_resource::<VerbRegistry>()
+            .world
+            .resource_mut::<VerbRegistry>()
+            .insert(spec);
+
+        self.insert_resource(VerbRoute::<T>(name, PhantomData))
+            .add_event::<VerbCall<T>>()
+            .add_system(
+                route_verb::<T>
+                    .label(Systems::Execute)
+                    .after(Systems::Parse),
+            )
+    }
+}
+
+/// Name of the verb that `VerbCall<T>` is sent for
+#[derive(Resource)]
+struct VerbRoute<T>(&'static str, PhantomData<fn() -> T>);
+
+fn route_verb<T: FromVerbArgs>(
+    route: Res<VerbRoute<T>>,
+    registry: Res<VerbRegistry>,
+    mut ev_issued: EventReader<CommandIssued>,
+    mut ev_call: EventWriter<VerbCall<T>>,
+    mut ev_rejected: EventWriter<CommandRejected>,
+) {
+    for ev in ev_issued.iter() {
+        let args = match &ev.command {
+            Command::Verb(args) if args.verb == route.0 => args,
+            _ => continue,
+        };
+
+        match T::from_args(args) {
+            Some(call_args) => ev_call.send(VerbCall {
+                input: ev.input.clone(),
+                side: ev.side,
+                command: ev.command.clone(),
+                args: call_args,
+            }),
+            None => {
+                error!("{} arguments don't match its VerbSpec", route.0);
+
+                let usage = registry
+                    .get(route.0)
+                    .map(VerbSpec::usage)
+                    .unwrap_or_else(|| route.0.to_string());
+
+                ev_rejected.send(CommandRejected {
+                    input: ev.input.clone(),
+                    side: ev.side,
+                    reason: RejectReason::Arguments(ArgError {
+                        verb: route.0,
+                        usage,
+                        kind: ArgErrorKind::Mismatch,
+                    }),
+                });
+            }
+        }
+    }
+}
+
+/// Built in `help`, lists every command
+pub(super) struct Help;
+
+impl FromVerbArgs for Help {
+    fn from_args(_: &VerbArgs) -> Option<Self> {
+        Some(Help)
+    }
+}
+
+pub(super) fn help(
+    registry: Res<VerbRegistry>,
+    mut ev_help: EventReader<VerbCall<Help>>,
+    mut ev_executed: EventWriter<CommandExecuted>,
+) {
+    for ev in ev_help.iter() {
+        let lines: Vec<String> = std::iter::once("<from>:<to>  move units, eg. B2:C3".to_string())
+            .chain(
+                registry
+                    .iter()
+                    .map(|verb| format!("{}  {}", verb.usage(), verb.help)),
+            )
+            .collect();
+
+        ev_executed.send(ev.executed_with(lines.join("\n")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build() -> VerbSpec {
+        VerbSpec::new("build", "")
+            .arg("cell", ArgKind::Coord)
+            .arg("kind", ArgKind::OneOf(&["tower", "wall"]))
+            .optional("count", ArgKind::Number)
+    }
+
+    fn coord(s: &str) -> GridCoord {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_every_kind() {
+        let args = build().parse(&["b2", "tower", "3"]).unwrap();
+
+        assert_eq!(args.coord(0), Some(coord("B2")));
+        assert_eq!(args.word(1), Some("tower"));
+        assert_eq!(args.number(2), Some(3));
+        assert_eq!(args.to_string(), "build B2 tower 3");
+        // Wrong kind for the index
+        assert_eq!(args.word(0), None);
+        assert_eq!(args.coord(5), None);
+    }
+
+    #[test]
+    fn missing_required_args() {
+        let error = build().parse(&["b2"]).unwrap_err();
+
+        assert_eq!(error.kind, ArgErrorKind::Missing("kind"));
+        assert_eq!(
+            error.to_string(),
+            "missing <kind>, usage: build <cell> <tower|wall> [count]"
+        );
+        assert_eq!(
+            build().parse(&[]).unwrap_err().kind,
+            ArgErrorKind::Missing("cell")
+        );
+    }
+
+    #[test]
+    fn optional_trailing_args() {
+        let args = build().parse(&["b2", "wall"]).unwrap();
+
+        assert_eq!(args.values.len(), 3);
+        assert_eq!(args.values[2], None);
+        assert_eq!(args.number(2), None);
+        assert_eq!(args.to_string(), "build B2 wall");
+    }
+
+    #[test]
+    fn too_many_args() {
+        let error = build().parse(&["b2", "wall", "1", "2"]).unwrap_err();
+
+        assert_eq!(error.kind, ArgErrorKind::TooMany(3));
+        assert!(error.to_string().starts_with("build takes 3 argument(s)"));
+
+        let error = VerbSpec::new("undo", "").parse(&["b2"]).unwrap_err();
+        assert_eq!(error.kind, ArgErrorKind::TooMany(0));
+    }
+
+    #[test]
+    fn one_of_ignores_case() {
+        let args = build().parse(&["B2", "WaLL"]).unwrap();
+        // The spec's spelling, not what was typed
+        assert_eq!(args.word(1), Some("wall"));
+
+        let error = build().parse(&["b2", "moat"]).unwrap_err();
+        assert_eq!(
+            error.kind,
+            ArgErrorKind::Invalid {
+                arg: "kind",
+                reason: "expected one of tower, wall".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_args() {
+        let error = build().parse(&["b2", "wall", "lots"]).unwrap_err();
+        assert_eq!(
+            error.kind,
+            ArgErrorKind::Invalid {
+                arg: "count",
+                reason: "expected a number".to_string(),
+            }
+        );
+
+        let error = build().parse(&["2b", "wall"]).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ArgErrorKind::Invalid { arg: "cell", .. }
+        ));
+    }
+
+    #[test]
+    fn ranges_take_a_single_coord() {
+        let spec = VerbSpec::new("clear", "").arg("cells", ArgKind::Range);
+
+        let args = spec.parse(&["a1-b2"]).unwrap();
+        assert_eq!(args.range(0), Some("A1-B2".parse().unwrap()));
+
+        let args = spec.parse(&["b2"]).unwrap();
+        assert_eq!(args.range(0), Some(GridRange::single(coord("B2"))));
+
+        // And a `Coord` argument can be read as a range too
+        let args = build().parse(&["b2", "wall"]).unwrap();
+        assert_eq!(args.range(0), Some(GridRange::single(coord("B2"))));
+        assert_eq!(args.range(1), None);
+    }
+
+    /// Reads a number where the spec says a cell, so it never matches
+    struct Mismatched;
+
+    impl FromVerbArgs for Mismatched {
+        fn from_args(args: &VerbArgs) -> Option<Self> {
+            args.number(0).map(|_| Mismatched)
+        }
+    }
+
+    #[test]
+    fn mismatched_args_are_rejected() {
+        let mut app = App::new();
+        app.add_event::<CommandIssued>()
+            .add_event::<CommandRejected>()
+            .add_verb::<Mismatched>(VerbSpec::new("mark", "").arg("cell", ArgKind::Coord));
+
+        let args = app
+            .world
+            .resource::<VerbRegistry>()
+            .get("MARK")
+            .unwrap()
+            .parse(&["b2"])
+            .unwrap();
+        app.world.send_event(CommandIssued {
+            input: "mark b2".to_string(),
+            side: Side::Defender,
+            command: Command::Verb(args),
+        });
+        app.update();
+
+        let calls = app.world.resource::<Events<VerbCall<Mismatched>>>();
+        assert!(calls.is_empty());
+
+        let rejected: Vec<CommandRejected> = app
+            .world
+            .resource_mut::<Events<CommandRejected>>()
+            .drain()
+            .collect();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].input, "mark b2");
+        assert_eq!(
+            rejected[0].reason,
+            RejectReason::Arguments(ArgError {
+                verb: "mark",
+                usage: "mark <cell>".to_string(),
+                kind: ArgErrorKind::Mismatch,
+            })
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::{prelude::*, render::camera::RenderTarget, window::WindowId};
+
+/// Plugin that tracks the cursor for every camera, taking `Camera::viewport`, the
+/// camera's window and its projection (eg. `PanCam` zoom) into account.
+///
+/// While a finger is on the screen its position is used for the primary window instead
+#[derive(Default)]
+pub struct CursorPlugin;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum CursorSystems {
+    Track,
+}
+
+/// Cursor position as seen by a single camera, added to every `Camera` automatically
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct CameraCursor {
+    /// Window the camera renders to, `None` for image render targets
+    pub window: Option<WindowId>,
+    /// Position in the camera's window with a top left origin, same as UI `Style` positions.
+    /// `None` while the cursor is outside the window
+    pub ui: Option<Vec2>,
+    /// Position in world space, `None` while the cursor is outside the camera's viewport
+    pub world: Option<Vec2>,
+}
+
+/// Cursor position from the camera the cursor is currently over. When viewports
+/// overlap the camera with the highest `Camera::priority` wins
+#[derive(Resource, Debug, Default)]
+pub struct MousePosition {
+    /// Position in the window with a top left origin, same as UI `Style` positions
+    pub cursor_ui: Vec2,
+    pub world: Vec2,
+    /// Camera the positions came from, `None` if the cursor isn't over any camera
+    pub camera: Option<Entity>,
+}
+
+/// Shortcut for `MousePosition::world`
+#[derive(Resource, Debug, Default)]
+pub struct WorldPosition(pub Vec2);
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MousePosition>()
+            .init_resource::<WorldPosition>()
+            .add_system(add_camera_cursor.before(CursorSystems::Track))
+            .add_system(track_cursor.label(CursorSystems::Track));
+    }
+}
+
+fn add_camera_cursor(
+    mut commands: Commands,
+    query: Query<Entity, (With<Camera>, Without<CameraCursor>)>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).insert(CameraCursor::default());
+    }
+}
+
+fn track_cursor(
+    windows: Res<Windows>,
+    touches: Res<Touches>,
+    mut cameras: Query<(Entity, &Camera, &GlobalTransform, &mut CameraCursor)>,
+    mut mouse_position: ResMut<MousePosition>,
+    mut world_position: ResMut<WorldPosition>,
+) {
+    let mut hovered: Option<(isize, Entity, Vec2, Vec2)> = None;
+
+    // Touches already have a top left origin. Fingers lifted this frame still count so
+    // taps land where they happened
+    let touch = touches.first_pressed_position().or_else(|| {
+        touches
+            .iter_just_released()
+            .next()
+            .map(|touch| touch.position())
+    });
+
+    for (entity, camera, transform, mut cursor) in cameras.iter_mut() {
+        let window = match &camera.target {
+            RenderTarget::Window(id) => windows.get(*id),
+            RenderTarget::Image(_) => None,
+        };
+
+        cursor.window = window.map(|w| w.id());
+
+        let ui = window.and_then(|window| match touch {
+            Some(touch) if window.id() == WindowId::primary() => Some(touch),
+            // Bevy's window cursor has a bottom left origin
+            _ => window
+                .cursor_position()
+                .map(|position| Vec2::new(position.x, window.height() - position.y)),
+        });
+
+        cursor.ui = ui;
+
+        let ui = match ui {
+            Some(ui) => ui,
+            None => {
+                cursor.world = None;
+                continue;
+            }
+        };
+
+        cursor.world = viewport_position(camera, ui)
+            .and_then(|position| camera.viewport_to_world(transform, position))
+            .map(|ray| ray.origin.truncate());
+
+        if let Some(world) = cursor.world {
+            let is_higher = hovered.is_none_or(|(priority, ..)| camera.priority > priority);
+
+            if is_higher {
+                hovered = Some((camera.priority, entity, ui, world));
+            }
+        }
+    }
+
+    match hovered {
+        Some((_, entity, ui, world)) => {
+            mouse_position.cursor_ui = ui;
+            mouse_position.world = world;
+            mouse_position.camera = Some(entity);
+            world_position.0 = world;
+        }
+        None => {
+            // Keep the last known positions, only forget the camera
+            if mouse_position.camera.is_some() {
+                mouse_position.camera = None;
+            }
+        }
+    }
+}
+
+/// Converts a top left origin window position to a bottom left origin position
+/// inside the camera's viewport, `None` when it is outside the viewport
+fn viewport_position(camera: &Camera, ui: Vec2) -> Option<Vec2> {
+    let (min, max) = camera.logical_viewport_rect()?;
+
+    if ui.cmplt(min).any() || ui.cmpgt(max).any() {
+        return None;
+    }
+
+    Some(Vec2::new(ui.x - min.x, max.y - ui.y))
+}
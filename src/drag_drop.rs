@@ -0,0 +1,350 @@
+use bevy::prelude::*;
+
+use crate::{
+    interaction::{Drag, DragEnd, DragStart, WorldInteractionSystemLabel},
+    picking::PickPriority,
+    spatial_index::SpatialIndex,
+};
+
+/// Plugin that moves `Draggable` entities around with the `WorldInteraction` drag
+/// events and drops them onto `DropTarget`s
+#[derive(Default)]
+pub struct DragDropPlugin;
+
+/// Label to allow ordering against `DragDropPlugin`
+#[derive(SystemLabel)]
+pub struct DragDropSystemLabel;
+
+/// Needs a `Bounds2` and `WorldInteraction` to receive the drag events
+#[derive(Component, Debug, Clone)]
+pub struct Draggable {
+    /// Matched against `DropTarget::accepts`
+    pub group: &'static str,
+    /// Return to where the drag started when the drop is rejected
+    pub snap_back: bool,
+}
+
+impl Default for Draggable {
+    fn default() -> Self {
+        Self {
+            group: "",
+            snap_back: true,
+        }
+    }
+}
+
+/// Needs to be in the `SpatialIndex`, eg. with a `Bounds2`. Overlapping targets are tried
+/// topmost first, by `PickPriority` and then z, until one accepts the item
+#[derive(Component, Debug, Clone)]
+pub struct DropTarget {
+    /// Groups of `Draggable` this target accepts, empty accepts everything
+    pub accepts: Vec<&'static str>,
+    /// Toggle off to reject everything, eg. when a slot is already filled
+    pub enabled: bool,
+    /// Move accepted items to the center of the target
+    pub snap_to_center: bool,
+}
+
+impl Default for DropTarget {
+    fn default() -> Self {
+        Self {
+            accepts: vec![],
+            enabled: true,
+            snap_to_center: true,
+        }
+    }
+}
+
+impl DropTarget {
+    pub fn accepting(groups: &[&'static str]) -> Self {
+        Self {
+            accepts: groups.to_vec(),
+            ..default()
+        }
+    }
+
+    pub fn accepts(&self, draggable: &Draggable) -> bool {
+        self.enabled && (self.accepts.is_empty() || self.accepts.contains(&draggable.group))
+    }
+}
+
+/// Added while an entity is being dragged
+#[derive(Component, Debug)]
+pub struct Dragging {
+    /// Local translation from before the drag started
+    pub origin: Vec3,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Dropped {
+    pub item: Entity,
+    pub target: Entity,
+}
+
+/// Sent when an item is let go without landing on a target that accepts it
+#[derive(Debug, Clone, Copy)]
+pub struct DropRejected {
+    pub item: Entity,
+    /// The target under the item, `None` if it was dropped on nothing
+    pub target: Option<Entity>,
+}
+
+impl Plugin for DragDropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Dropped>()
+            .add_event::<DropRejected>()
+            .add_system(
+                start_drag
+                    .label(DragDropSystemLabel)
+                    .after(WorldInteractionSystemLabel),
+            )
+            .add_system(move_dragged.label(DragDropSystemLabel).after(start_drag))
+            .add_system(drop_dragged.label(DragDropSystemLabel).after(move_dragged));
+    }
+}
+
+fn start_drag(
+    mut commands: Commands,
+    mut ev_drag_start: EventReader<DragStart>,
+    query: Query<&Transform, With<Draggable>>,
+) {
+    for ev in ev_drag_start.iter() {
+        if let Ok(transform) = query.get(ev.entity) {
+            commands.entity(ev.entity).insert(Dragging {
+                origin: transform.translation,
+            });
+        }
+    }
+}
+
+fn move_dragged(
+    mut ev_drag: EventReader<Drag>,
+    mut query: Query<(&mut Transform, Option<&Parent>), With<Draggable>>,
+    parents: Query<&GlobalTransform>,
+) {
+    for ev in ev_drag.iter() {
+        if let Ok((mut transform, parent)) = query.get_mut(ev.entity) {
+            // The delta is in world space, bring it into the parent's space first
+            let delta = match parent.and_then(|p| parents.get(p.get()).ok()) {
+                Some(parent) => parent
+                    .affine()
+                    .inverse()
+                    .transform_vector3(ev.delta.extend(0.0)),
+                None => ev.delta.extend(0.0),
+            };
+
+            transform.translation += delta;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn drop_dragged(
+    mut commands: Commands,
+    mut ev_drag_end: EventReader<DragEnd>,
+    mut items: Query<(&Draggable, &Dragging, &mut Transform, Option<&Parent>)>,
+    targets: Query<(&DropTarget, &GlobalTransform, Option<&PickPriority>)>,
+    parents: Query<&GlobalTransform>,
+    index: Res<SpatialIndex>,
+    mut ev_dropped: EventWriter<Dropped>,
+    mut ev_drop_rejected: EventWriter<DropRejected>,
+) {
+    for ev in ev_drag_end.iter() {
+        let (draggable, dragging, mut transform, parent) = match items.get_mut(ev.entity) {
+            Ok(item) => item,
+            Err(_) => continue,
+        };
+
+        commands.entity(ev.entity).remove::<Dragging>();
+
+        // The dragged item is under the cursor as well, so skip it
+        let candidates = index
+            .entities_at_point(ev.position)
+            .into_iter()
+            .filter(|e| *e != ev.entity)
+            .filter_map(|e| {
+                let (target, transform, priority) = targets.get(e).ok()?;
+                let priority = priority.copied().unwrap_or_default();
+
+                Some((e, target, priority, transform.translation().z))
+            })
+            .collect();
+
+        match resolve_drop(draggable, candidates) {
+            Ok(target) => {
+                let (drop_target, target_transform, _) = targets.get(target).unwrap();
+
+                if drop_target.snap_to_center {
+                    let world = target_transform.translation().truncate();
+                    let local = match parent.and_then(|p| parents.get(p.get()).ok()) {
+                        Some(parent) => parent
+                            .affine()
+                            .inverse()
+                            .transform_point3(world.extend(0.0)),
+                        None => world.extend(0.0),
+                    };
+
+                    transform.translation.x = local.x;
+                    transform.translation.y = local.y;
+                }
+
+                ev_dropped.send(Dropped {
+                    item: ev.entity,
+                    target,
+                });
+            }
+            Err(target) => {
+                if draggable.snap_back {
+                    transform.translation = dragging.origin;
+                }
+
+                ev_drop_rejected.send(DropRejected {
+                    item: ev.entity,
+                    target,
+                });
+            }
+        }
+    }
+}
+
+/// Entity, target, `PickPriority` and z of a `DropTarget` under the cursor
+type DropCandidate<'a> = (Entity, &'a DropTarget, PickPriority, f32);
+
+/// The topmost target that accepts the item, a rejecting one on top doesn't hide the
+/// targets under it. Otherwise the topmost target, if there was one, for `DropRejected`
+fn resolve_drop(
+    draggable: &Draggable,
+    mut candidates: Vec<DropCandidate>,
+) -> Result<Entity, Option<Entity>> {
+    candidates.sort_by(|a, b| b.2.cmp(&a.2).then(b.3.total_cmp(&a.3)));
+
+    candidates
+        .iter()
+        .find(|(_, target, _, _)| target.accepts(draggable))
+        .map(|(entity, _, _, _)| *entity)
+        .ok_or_else(|| candidates.first().map(|(entity, _, _, _)| *entity))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bounds::Bounds2, spatial_index::SpatialIndexPlugin};
+
+    use super::*;
+
+    fn entity(id: u32) -> Entity {
+        Entity::from_raw(id)
+    }
+
+    #[test]
+    fn topmost_accepting_target_wins() {
+        let cards = Draggable {
+            group: "card",
+            ..default()
+        };
+        let slot = DropTarget::accepting(&["card"]);
+        let overlay = DropTarget::accepting(&["gem"]);
+        let candidates = vec![
+            (entity(1), &slot, PickPriority(0), 1.0),
+            (entity(2), &overlay, PickPriority(0), 5.0),
+            (entity(3), &slot, PickPriority(0), 2.0),
+        ];
+
+        // The overlay on top rejects cards, the highest slot under it takes the card
+        assert_eq!(resolve_drop(&cards, candidates), Ok(entity(3)));
+    }
+
+    #[test]
+    fn priority_beats_z() {
+        let item = Draggable::default();
+        let target = DropTarget::default();
+        let candidates = vec![
+            (entity(1), &target, PickPriority(0), 10.0),
+            (entity(2), &target, PickPriority(1), 0.0),
+        ];
+
+        assert_eq!(resolve_drop(&item, candidates), Ok(entity(2)));
+    }
+
+    #[test]
+    fn rejected_by_everything_reports_the_topmost() {
+        let item = Draggable::default();
+        let disabled = DropTarget {
+            enabled: false,
+            ..default()
+        };
+        let candidates = vec![
+            (entity(1), &disabled, PickPriority(0), 1.0),
+            (entity(2), &disabled, PickPriority(0), 3.0),
+        ];
+
+        assert_eq!(resolve_drop(&item, candidates), Err(Some(entity(2))));
+        assert_eq!(resolve_drop(&item, vec![]), Err(None));
+    }
+
+    #[test]
+    fn drops_through_a_rejecting_overlay() {
+        let mut app = App::new();
+        app.add_event::<DragStart>()
+            .add_event::<Drag>()
+            .add_event::<DragEnd>()
+            .add_plugin(SpatialIndexPlugin::default())
+            .add_plugin(DragDropPlugin);
+
+        let square = Bounds2 {
+            position: Vec2::new(100.0, 0.0),
+            size: Vec2::splat(50.0),
+        };
+        let slot = app
+            .world
+            .spawn((
+                DropTarget::accepting(&["card"]),
+                square,
+                GlobalTransform::from_xyz(100.0, 0.0, 0.0),
+            ))
+            .id();
+        let overlay = app
+            .world
+            .spawn((
+                DropTarget::accepting(&["gem"]),
+                square,
+                GlobalTransform::from_xyz(100.0, 0.0, 5.0),
+            ))
+            .id();
+        let card = app
+            .world
+            .spawn((
+                Draggable {
+                    group: "card",
+                    ..default()
+                },
+                Transform::default(),
+            ))
+            .id();
+
+        app.world.send_event(DragStart {
+            entity: card,
+            position: Vec2::ZERO,
+        });
+        app.update();
+
+        app.world.send_event(DragEnd {
+            entity: card,
+            position: Vec2::new(110.0, 10.0),
+        });
+        app.update();
+
+        let dropped: Vec<(Entity, Entity)> = app
+            .world
+            .resource_mut::<Events<Dropped>>()
+            .drain()
+            .map(|ev| (ev.item, ev.target))
+            .collect();
+        assert_eq!(dropped, vec![(card, slot)]);
+        assert_ne!(slot, overlay);
+
+        // Snapped to the slot's center
+        let transform = app.world.get::<Transform>(card).unwrap();
+        assert_eq!(transform.translation.truncate(), Vec2::new(100.0, 0.0));
+        assert!(app.world.get::<Dragging>(card).is_none());
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{
+    bounds::Bounds2,
+    cursor::WorldPosition,
+    picking::{Picked, PickingSystemLabel},
+};
+use bevy::prelude::*;
+
+/// Plugin that tracks `WorldInteraction` for entities with `Bounds2` and sends
+/// hover/press/click/drag events for them. Only the entities from `Picked` are
+/// interacted with, so `PickingPlugin` needs to be added too
+pub struct WorldInteractionPlugin {
+    /// Button that presses, clicks and drags
+    pub button: MouseButton,
+    /// How far in world units the cursor has to move while pressed before it
+    /// counts as a drag instead of a click
+    pub drag_threshold: f32,
+}
+
+impl Default for WorldInteractionPlugin {
+    fn default() -> Self {
+        Self {
+            button: MouseButton::Left,
+            drag_threshold: 4.0,
+        }
+    }
+}
+
+/// Label to allow ordering against `WorldInteractionPlugin`
+#[derive(SystemLabel)]
+pub struct WorldInteractionSystemLabel;
+
+/// World space version of Bevy UI's `Interaction`. Add it next to a `Bounds2` to
+/// opt the entity into interaction events
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WorldInteraction {
+    #[default]
+    None,
+    Hovered,
+    Pressed,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HoverEnter(pub Entity);
+
+#[derive(Debug, Clone, Copy)]
+pub struct HoverExit(pub Entity);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Pressed {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+/// Pressed and released over the same entity without dragging
+#[derive(Debug, Clone, Copy)]
+pub struct Clicked {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DragStart {
+    pub entity: Entity,
+    /// Where the press started, not where the drag threshold was crossed
+    pub position: Vec2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Drag {
+    pub entity: Entity,
+    pub position: Vec2,
+    /// Movement since the last `Drag` (or `DragStart`) event
+    pub delta: Vec2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DragEnd {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+#[derive(Resource)]
+struct InteractionSettings {
+    button: MouseButton,
+    drag_threshold: f32,
+}
+
+#[derive(Resource, Default)]
+struct InteractionState {
+    hovered: Vec<Entity>,
+    pressed: Vec<Entity>,
+    press_position: Vec2,
+    last_position: Vec2,
+    dragging: bool,
+}
+
+impl Plugin for WorldInteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InteractionSettings {
+            button: self.button,
+            drag_threshold: self.drag_threshold,
+        })
+        .init_resource::<InteractionState>()
+        .add_event::<HoverEnter>()
+        .add_event::<HoverExit>()
+        .add_event::<Pressed>()
+        .add_event::<Clicked>()
+        .add_event::<DragStart>()
+        .add_event::<Drag>()
+        .add_event::<DragEnd>()
+        .add_system(
+            update_interactions
+                .label(WorldInteractionSystemLabel)
+                .after(PickingSystemLabel),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_interactions(
+    mut query: Query<&mut WorldInteraction, With<Bounds2>>,
+    picked: Res<Picked>,
+    mouse_position: Res<WorldPosition>,
+    buttons: Res<Input<MouseButton>>,
+    settings: Res<InteractionSettings>,
+    mut state: ResMut<InteractionState>,
+    mut ev_hover_enter: EventWriter<HoverEnter>,
+    mut ev_hover_exit: EventWriter<HoverExit>,
+    mut ev_pressed: EventWriter<Pressed>,
+    mut ev_clicked: EventWriter<Clicked>,
+    mut ev_drag_start: EventWriter<DragStart>,
+    mut ev_drag: EventWriter<Drag>,
+    mut ev_drag_end: EventWriter<DragEnd>,
+) {
+    let position = mouse_position.0;
+    let under_cursor: Vec<Entity> = picked
+        .entities
+        .iter()
+        .copied()
+        .filter(|entity| query.contains(*entity))
+        .collect();
+
+    // Anything that could need its `WorldInteraction` updated at the end
+    let touched: Vec<Entity> = state
+        .hovered
+        .iter()
+        .chain(state.pressed.iter())
+        .copied()
+        .collect();
+
+    // Hover
+    for entity in state.hovered.iter().filter(|e| !under_cursor.contains(e)) {
+        ev_hover_exit.send(HoverExit(*entity));
+    }
+
+    for entity in under_cursor.iter().filter(|e| !state.hovered.contains(e)) {
+        ev_hover_enter.send(HoverEnter(*entity));
+    }
+
+    // Press
+    if buttons.just_pressed(settings.button) {
+        for entity in under_cursor.iter() {
+            ev_pressed.send(Pressed {
+                entity: *entity,
+                position,
+            });
+        }
+
+        state.pressed = under_cursor.clone();
+        state.press_position = position;
+        state.last_position = position;
+        state.dragging = false;
+    }
+
+    // Drag
+    if buttons.pressed(settings.button) && !state.pressed.is_empty() {
+        let moved = position.distance(state.press_position);
+
+        if !state.dragging && moved > settings.drag_threshold {
+            state.dragging = true;
+
+            for entity in state.pressed.iter() {
+                ev_drag_start.send(DragStart {
+                    entity: *entity,
+                    position: state.press_position,
+                });
+            }
+        }
+
+        if state.dragging && position != state.last_position {
+            for entity in state.pressed.iter() {
+                ev_drag.send(Drag {
+                    entity: *entity,
+                    position,
+                    delta: position - state.last_position,
+                });
+            }
+
+            state.last_position = position;
+        }
+    }
+
+    // Release
+    if buttons.just_released(settings.button) {
+        for entity in state.pressed.iter() {
+            if state.dragging {
+                ev_drag_end.send(DragEnd {
+                    entity: *entity,
+                    position,
+                });
+            } else if under_cursor.contains(entity) {
+                ev_clicked.send(Clicked {
+                    entity: *entity,
+                    position,
+                });
+            }
+        }
+
+        state.pressed.clear();
+        state.dragging = false;
+    }
+
+    // Pressed entities stay pressed while dragging outside of their bounds
+    for entity in touched.iter().chain(under_cursor.iter()) {
+        if let Ok(mut interaction) = query.get_mut(*entity) {
+            let next = if state.pressed.contains(entity) {
+                WorldInteraction::Pressed
+            } else if under_cursor.contains(entity) {
+                WorldInteraction::Hovered
+            } else {
+                WorldInteraction::None
+            };
+
+            if *interaction != next {
+                *interaction = next;
+            }
+        }
+    }
+
+    state.hovered = under_cursor;
+}
@@ -0,0 +1,16 @@
+//! Plugins and small games for trying out Bevy, `main.rs` puts some of them together
+
+pub mod bounds;
+pub mod bounds_sync;
+pub mod command_defender;
+pub mod cursor;
+pub mod drag_drop;
+pub mod interaction;
+pub mod mouse_over;
+pub mod pancam;
+pub mod picking;
+pub mod select_area;
+pub mod solar_system;
+pub mod spatial_index;
+pub mod tooltip;
+pub mod touch;
@@ -1,18 +1,23 @@
 use bevy::prelude::*;
-use bevy_mouse_position::MousePositionPlugin;
-use mouse_over::MouseOverPlugin;
-
-use pancam::{PanCam, PanCamPlugin};
-// use command_defender::CommandDefenderPlugin;
-// use select_area::SelectAreaPlugin;
-// use solar_system::SolarSystemPlugin;
-
-mod bounds;
-mod command_defender;
-mod mouse_over;
-mod pancam;
-mod select_area;
-mod solar_system;
+use bevy_book_game::{
+    bounds_sync::BoundsSyncPlugin,
+    command_defender::{
+        net::{NetRole, NetSession, NetworkPlugin},
+        script::{run_headless, CommandSource},
+        CommandDefenderPlugin,
+    },
+    cursor::CursorPlugin,
+    drag_drop::DragDropPlugin,
+    interaction::WorldInteractionPlugin,
+    mouse_over::MouseOverPlugin,
+    pancam::{PanCam, PanCamPlugin},
+    picking::PickingPlugin,
+    spatial_index::SpatialIndexPlugin,
+    tooltip::TooltipPlugin,
+    touch::TouchPlugin,
+};
+// use bevy_book_game::select_area::SelectAreaPlugin;
+// use bevy_book_game::solar_system::SolarSystemPlugin;
 
 const WINDOW_TITLE: &str = "Commander Defender";
 const WINDOW_WIDTH: f32 = 1133.0;
@@ -34,24 +39,85 @@ fn spawn_camera(mut commands: Commands) {
 }
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            window: WindowDescriptor {
-                width: WINDOW_WIDTH,
-                height: WINDOW_HEIGHT,
-                title: WINDOW_TITLE.to_string(),
-                ..default()
+    // `cargo run -- --headless [script.cmds]` plays Command Defender from a script or stdin,
+    // `--host <address>` or `--join <address>` in front plays it against another game and
+    // `--seed <number>` picks the waves of a headless game
+    let mut args = std::env::args().skip(1).peekable();
+    let mut role = None;
+    let mut headless = None;
+    let mut seed = None;
+
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.peek()) {
+            ("--host", Some(address)) => role = Some(NetRole::Host(address.clone())),
+            ("--join", Some(address)) => role = Some(NetRole::Join(address.clone())),
+            ("--seed", Some(number)) => match number.parse() {
+                Ok(number) => seed = Some(number),
+                Err(_) => {
+                    eprintln!("--seed takes a number, got {}", number);
+                    return;
+                }
             },
+            ("--headless", Some(path)) if !path.starts_with("--") => {
+                headless = Some(CommandSource::File(path.into()))
+            }
+            ("--headless", _) => {
+                headless = Some(CommandSource::Stdin);
+                continue;
+            }
+            _ => {
+                eprintln!("Unknown argument {}", arg);
+                return;
+            }
+        }
+        args.next();
+    }
+
+    let session = match role.as_ref().map(NetSession::connect) {
+        Some(Ok(session)) => Some(session),
+        Some(Err(error)) => {
+            eprintln!("Couldn't connect to the other player: {}", error);
+            return;
+        }
+        None => None,
+    };
+
+    if let Some(source) = headless {
+        return run_headless(source, session, seed);
+    }
+
+    let mut app = App::new();
+
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        window: WindowDescriptor {
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            title: WINDOW_TITLE.to_string(),
             ..default()
-        }))
-        .add_plugin(PanCamPlugin::default())
-        .add_plugin(MousePositionPlugin)
-        .add_plugin(MouseOverPlugin)
-        // .add_plugin(CommandDefenderPlugin)
-        // .add_plugin(SelectAreaPlugin)
-        .insert_resource(ClearColor(BACKGROUND_COLOR))
-        .add_startup_system(spawn_camera)
-        // .add_plugin(SolarSystemPlugin)
-        .add_system(bevy::window::close_on_esc)
-        .run();
+        },
+        ..default()
+    }))
+    .add_plugin(PanCamPlugin)
+    .add_plugin(CursorPlugin)
+    .add_plugin(TouchPlugin::default())
+    .add_plugin(BoundsSyncPlugin)
+    .add_plugin(SpatialIndexPlugin::default())
+    .add_plugin(PickingPlugin)
+    .add_plugin(WorldInteractionPlugin::default())
+    .add_plugin(DragDropPlugin)
+    .add_plugin(TooltipPlugin::default())
+    .add_plugin(MouseOverPlugin)
+    // .add_plugin(CommandDefenderPlugin::default())
+    // .add_plugin(SelectAreaPlugin)
+    // .add_plugin(SolarSystemPlugin)
+    // Escape is left alone, it takes the focus away from the command bar
+    .insert_resource(ClearColor(BACKGROUND_COLOR))
+    .add_startup_system(spawn_camera);
+
+    if let Some(session) = session {
+        app.add_plugin(session.game_plugin(CommandDefenderPlugin::default()))
+            .add_plugin(NetworkPlugin::new(session));
+    }
+
+    app.run();
 }
@@ -1,21 +1,28 @@
-use bevy::prelude::*;
-use bevy_mouse_position::WorldPosition;
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
-use crate::bounds::Bounds2;
+use crate::{
+    bounds::{Bounds2, OrientedBounds2, Shape2, ShapeBounds2},
+    bounds_sync::SyncBounds,
+    cursor::WorldPosition,
+    interaction::{HoverEnter, HoverExit, WorldInteraction},
+};
 
 pub struct MouseOverPlugin;
 
 impl Plugin for MouseOverPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(spawn_square)
-            .add_system(hover_square);
+            .add_startup_system(spawn_rotated_square)
+            .add_startup_system(spawn_shapes)
+            .add_system(hover_square)
+            .add_system(hover_rotated_square)
+            .add_system(hover_shapes);
     }
 }
 
 fn spawn_square(mut commands: Commands) {
     let size = Vec2::new(200.0, 100.0);
-    let position = Vec2::new(0.0, 0.0);
-    let transform = Transform::from_xyz(position.x, position.y, 0.0);
+    let transform = Transform::from_xyz(0.0, 0.0, 0.0);
 
     commands.spawn((
         SpriteBundle {
@@ -27,16 +34,112 @@ fn spawn_square(mut commands: Commands) {
             transform,
             ..default()
         },
-        Bounds2 { position, size },
+        Bounds2::default(),
+        SyncBounds,
+        WorldInteraction::default(),
+        Name::new("Square"),
     ));
 }
 
-fn hover_square(mut query: Query<(&mut Sprite, &Bounds2)>, mouse_position: Res<WorldPosition>) {
-    for (mut sprite, bounds) in query.iter_mut() {
-        if bounds.in_bounds_centered(mouse_position.0) {
+fn spawn_rotated_square(mut commands: Commands) {
+    let size = Vec2::new(150.0, 50.0);
+    let transform = Transform::from_xyz(0.0, 200.0, 0.0)
+        .with_rotation(Quat::from_rotation_z(f32::to_radians(30.0)));
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::RED,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform,
+            ..default()
+        },
+        OrientedBounds2::new(size),
+    ));
+}
+
+fn spawn_shapes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let radius = 50.0;
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+            material: materials.add(ColorMaterial::from(Color::RED)),
+            transform: Transform::from_xyz(-250.0, 0.0, 0.0),
+            ..default()
+        },
+        ShapeBounds2(Shape2::Circle(radius)),
+    ));
+
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes
+                .add(shape::RegularPolygon::new(radius, 6).into())
+                .into(),
+            material: materials.add(ColorMaterial::from(Color::RED)),
+            transform: Transform::from_xyz(250.0, 0.0, 0.0),
+            ..default()
+        },
+        ShapeBounds2(Shape2::hexagon(radius)),
+    ));
+}
+
+fn hover_square(
+    mut query: Query<&mut Sprite, With<Bounds2>>,
+    mut ev_hover_enter: EventReader<HoverEnter>,
+    mut ev_hover_exit: EventReader<HoverExit>,
+) {
+    for HoverExit(entity) in ev_hover_exit.iter() {
+        if let Ok(mut sprite) = query.get_mut(*entity) {
+            sprite.color = Color::RED;
+        }
+    }
+
+    for HoverEnter(entity) in ev_hover_enter.iter() {
+        if let Ok(mut sprite) = query.get_mut(*entity) {
             sprite.color = Color::GREEN;
+        }
+    }
+}
+
+fn hover_rotated_square(
+    mut query: Query<(
+        &mut Sprite,
+        &mut Transform,
+        &GlobalTransform,
+        &OrientedBounds2,
+    )>,
+    mouse_position: Res<WorldPosition>,
+) {
+    for (mut sprite, mut transform, global_transform, bounds) in query.iter_mut() {
+        if bounds.in_bounds(global_transform, mouse_position.0) {
+            sprite.color = Color::GREEN;
+            transform.scale = transform.scale.lerp(Vec3::new(1.5, 1.5, 1.0), 0.2);
         } else {
             sprite.color = Color::RED;
+            transform.scale = Vec3::ONE;
+        }
+    }
+}
+
+fn hover_shapes(
+    query: Query<(&Handle<ColorMaterial>, &GlobalTransform, &ShapeBounds2)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mouse_position: Res<WorldPosition>,
+) {
+    for (handle, transform, bounds) in query.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            if bounds.in_bounds(transform, mouse_position.0) {
+                material.color = Color::GREEN;
+            } else {
+                material.color = Color::RED;
+            }
         }
     }
 }
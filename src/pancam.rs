@@ -6,6 +6,8 @@ use bevy::{
     render::camera::OrthographicProjection,
 };
 
+use crate::touch::{TouchGesture, TouchSystemLabel};
+
 /// Plugin that adds the necessary systems for `PanCam` components to work
 #[derive(Default)]
 pub struct PanCamPlugin;
@@ -17,7 +19,12 @@ pub struct PanCamSystemLabel;
 impl Plugin for PanCamPlugin {
     fn build(&self, app: &mut App) {
         app.add_system(camera_movement.label(PanCamSystemLabel))
-            .add_system(camera_zoom.label(PanCamSystemLabel));
+            .add_system(camera_zoom.label(PanCamSystemLabel))
+            .add_system(
+                camera_touch
+                    .label(PanCamSystemLabel)
+                    .after(TouchSystemLabel),
+            );
 
         app.register_type::<PanCam>();
     }
@@ -207,6 +214,99 @@ fn camera_movement(
     *last_pos = Some(current_pos);
 }
 
+/// One finger pans and two finger pinch zooms, needs the `TouchPlugin` to do anything
+fn camera_touch(
+    windows: Res<Windows>,
+    gesture: Option<Res<TouchGesture>>,
+    mut query: Query<(&PanCam, &mut OrthographicProjection, &mut Transform)>,
+) {
+    let gesture = match gesture {
+        Some(gesture) => *gesture,
+        None => return,
+    };
+
+    let window = windows.get_primary().unwrap();
+    let window_size = Vec2::new(window.width(), window.height());
+
+    for (cam, mut proj, mut transform) in &mut query {
+        if !cam.enabled {
+            continue;
+        }
+
+        match gesture {
+            TouchGesture::Pan { delta } => {
+                let proj_size =
+                    Vec2::new(proj.right - proj.left, proj.top - proj.bottom) * proj.scale;
+                let world_units_per_device_pixel = proj_size / window_size;
+
+                // Touch positions have a top left origin, so y is flipped compared to the world
+                let delta_world = Vec2::new(delta.x, -delta.y) * world_units_per_device_pixel;
+                transform.translation -= delta_world.extend(0.);
+            }
+            TouchGesture::Pinch { center, ratio } if ratio.is_finite() && ratio > 0. => {
+                let old_scale = proj.scale;
+                proj.scale = (proj.scale / ratio).max(cam.min_scale);
+
+                if let Some(max_scale) = cam.max_scale {
+                    proj.scale = proj.scale.min(max_scale);
+                }
+                if let (Some(min_x_bound), Some(max_x_bound)) = (cam.min_x, cam.max_x) {
+                    let max_safe_scale = max_scale_within_x_bounds(min_x_bound, max_x_bound, &proj);
+                    proj.scale = proj.scale.min(max_safe_scale);
+                }
+                if let (Some(min_y_bound), Some(max_y_bound)) = (cam.min_y, cam.max_y) {
+                    let max_safe_scale = max_scale_within_y_bounds(min_y_bound, max_y_bound, &proj);
+                    proj.scale = proj.scale.min(max_safe_scale);
+                }
+
+                // Same as zooming to the mouse cursor, keep the point between the fingers in place
+                if cam.zoom_to_cursor {
+                    let normalized_center = Vec2::new(
+                        center.x / window_size.x * 2. - 1.,
+                        1. - center.y / window_size.y * 2.,
+                    );
+                    let proj_size = Vec2::new(proj.right, proj.top);
+                    let center_world = transform.translation.truncate()
+                        + normalized_center * proj_size * old_scale;
+
+                    transform.translation = (center_world
+                        - normalized_center * proj_size * proj.scale)
+                        .extend(transform.translation.z);
+                }
+            }
+            _ => continue,
+        }
+
+        let half_of_viewport =
+            Vec2::new(proj.right - proj.left, proj.top - proj.bottom) * proj.scale / 2.;
+
+        if let Some(min_x_bound) = cam.min_x {
+            transform.translation.x = transform
+                .translation
+                .x
+                .max(min_x_bound + half_of_viewport.x);
+        }
+        if let Some(max_x_bound) = cam.max_x {
+            transform.translation.x = transform
+                .translation
+                .x
+                .min(max_x_bound - half_of_viewport.x);
+        }
+        if let Some(min_y_bound) = cam.min_y {
+            transform.translation.y = transform
+                .translation
+                .y
+                .max(min_y_bound + half_of_viewport.y);
+        }
+        if let Some(max_y_bound) = cam.max_y {
+            transform.translation.y = transform
+                .translation
+                .y
+                .min(max_y_bound - half_of_viewport.y);
+        }
+    }
+}
+
 /// A component that adds panning camera controls to an orthographic camera
 #[derive(Component, Reflect)]
 #[reflect(Component)]
@@ -0,0 +1,123 @@
+use crate::{
+    bounds::{OrientedBounds2, ShapeBounds2},
+    cursor::{CursorSystems, WorldPosition},
+    spatial_index::SpatialIndex,
+};
+use bevy::{prelude::*, ui::FocusPolicy};
+
+/// Plugin that works out which entities in the `SpatialIndex` are under the cursor,
+/// topmost first
+#[derive(Default)]
+pub struct PickingPlugin;
+
+/// Label to allow ordering against `PickingPlugin`
+#[derive(SystemLabel)]
+pub struct PickingSystemLabel;
+
+/// Entities with this marker are still picked but don't block the entities under them
+#[derive(Component, Default)]
+pub struct PickPassThrough;
+
+/// Picked ahead of entities with a lower priority, whatever their z. Entities without
+/// it have a priority of 0
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PickPriority(pub i32);
+
+/// Result of the current frame's picking.
+///
+/// Ordered topmost first by `PickPriority` and then `GlobalTransform` z. Stops at the
+/// first entity without `PickPassThrough`, so most of the time there is only one.
+#[derive(Resource, Debug, Default)]
+pub struct Picked {
+    pub entities: Vec<Entity>,
+    /// The cursor is over a UI node, nothing in the world gets picked
+    pub blocked_by_ui: bool,
+}
+
+impl Picked {
+    pub fn topmost(&self) -> Option<Entity> {
+        self.entities.first().copied()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+}
+
+type PickCandidate<'a> = (
+    &'a GlobalTransform,
+    Option<&'a PickPriority>,
+    Option<&'a OrientedBounds2>,
+    Option<&'a ShapeBounds2>,
+    Option<&'a ComputedVisibility>,
+    Option<&'a PickPassThrough>,
+);
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Picked>().add_system(
+            update_picked
+                .label(PickingSystemLabel)
+                .after(CursorSystems::Track),
+        );
+    }
+}
+
+fn update_picked(
+    candidates: Query<PickCandidate>,
+    ui_nodes: Query<(&Interaction, Option<&FocusPolicy>), With<Node>>,
+    index: Res<SpatialIndex>,
+    mouse_position: Res<WorldPosition>,
+    mut picked: ResMut<Picked>,
+) {
+    // `Interaction` is only updated for nodes under the cursor, eg. the buttons
+    picked.blocked_by_ui = ui_nodes.iter().any(|(interaction, focus_policy)| {
+        *interaction != Interaction::None && focus_policy != Some(&FocusPolicy::Pass)
+    });
+
+    picked.entities.clear();
+
+    if picked.blocked_by_ui {
+        return;
+    }
+
+    let point = mouse_position.0;
+    let mut hits: Vec<(Entity, PickPriority, f32, bool)> = index
+        .entities_at_point(point)
+        .into_iter()
+        .filter_map(|entity| {
+            let (transform, priority, oriented, shape, visibility, pass_through) =
+                candidates.get(entity).ok()?;
+
+            if visibility.is_some_and(|v| !v.is_visible_in_hierarchy()) {
+                return None;
+            }
+
+            // `Bounds2` is only the axis aligned box, use the exact shape if there is one
+            let exact_hit = match (oriented, shape) {
+                (_, Some(shape)) => shape.in_bounds(transform, point),
+                (Some(oriented), None) => oriented.in_bounds(transform, point),
+                (None, None) => true,
+            };
+
+            if !exact_hit {
+                return None;
+            }
+
+            let priority = priority.copied().unwrap_or_default();
+            let z = transform.translation().z;
+
+            Some((entity, priority, z, pass_through.is_some()))
+        })
+        .collect();
+
+    hits.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.total_cmp(&a.2)));
+
+    for (entity, _, _, pass_through) in hits {
+        picked.entities.push(entity);
+
+        if !pass_through {
+            break;
+        }
+    }
+}
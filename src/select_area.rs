@@ -1,6 +1,11 @@
 use bevy::prelude::*;
-// use bevy_mouse_position::MousePosition;
-use bevy_mouse_position::{MousePosition, MousePositionSystems};
+
+use crate::{
+    bounds::Bounds2,
+    cursor::{CameraCursor, CursorSystems, MousePosition},
+    spatial_index::SpatialIndex,
+    touch::TouchGesture,
+};
 
 pub struct SelectAreaPlugin;
 
@@ -10,12 +15,15 @@ struct RegionState {
     start: (Vec2, Vec2),
     /// A tuple containing the UI cursor position (0) and World cursor position (1)
     end: (Vec2, Vec2),
+    /// The camera the region was started in, the rest of the drag uses the same camera
+    camera: Option<Entity>,
 }
 
 #[derive(Component)]
 struct Region;
 
-#[derive(Component)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
 pub struct Selectable;
 
 #[derive(Resource, Debug, Default)]
@@ -30,12 +38,13 @@ struct ConfirmationEvent {
 impl Plugin for SelectAreaPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(SelectedEntities::default())
+            .register_type::<Selectable>()
             .add_startup_system(setup_tracking_region)
             .add_event::<ConfirmationEvent>()
             .add_system(
                 track_region
                     .label("track_region")
-                    .after(MousePositionSystems::Track),
+                    .after(CursorSystems::Track),
             )
             .add_system(draw_region.after("track_region"))
             .add_system(capture_region_confirmation.after("track_region"));
@@ -61,19 +70,33 @@ fn setup_tracking_region(mut commands: Commands) {
 
 fn track_region(
     buttons: Res<Input<MouseButton>>,
+    gesture: Option<Res<TouchGesture>>,
+    mut was_long_press: Local<bool>,
     mouse_position: Res<MousePosition>,
+    cameras: Query<&CameraCursor>,
     mut query: Query<(&mut RegionState, &mut Visibility), With<Region>>,
     mut ev_confirmation: EventWriter<ConfirmationEvent>,
 ) {
     let (mut region, mut visible) = query.single_mut();
 
-    if buttons.just_pressed(MouseButton::Right) {
+    // A long press drag on touch screens works the same as holding right click
+    let is_long_press =
+        gesture.is_some_and(|gesture| matches!(*gesture, TouchGesture::LongPressDrag { .. }));
+    let just_pressed =
+        buttons.just_pressed(MouseButton::Right) || (is_long_press && !*was_long_press);
+    let just_released =
+        buttons.just_released(MouseButton::Right) || (!is_long_press && *was_long_press);
+    let pressed = buttons.pressed(MouseButton::Right) || is_long_press;
+    *was_long_press = is_long_press;
+
+    if just_pressed {
         region.start = (mouse_position.cursor_ui, mouse_position.world);
         region.end = (mouse_position.cursor_ui, mouse_position.world);
+        region.camera = mouse_position.camera;
         visible.is_visible = true;
     }
 
-    if buttons.just_released(MouseButton::Right) {
+    if just_released {
         ev_confirmation.send(ConfirmationEvent {
             start: region.start.1,
             end: region.end.1,
@@ -81,11 +104,22 @@ fn track_region(
 
         region.start = (Vec2::default(), Vec2::default());
         region.end = (Vec2::default(), Vec2::default());
+        region.camera = None;
         visible.is_visible = false;
     }
 
-    if buttons.pressed(MouseButton::Right) {
-        region.end = (mouse_position.cursor_ui, mouse_position.world);
+    if pressed {
+        let cursor = region.camera.and_then(|camera| cameras.get(camera).ok());
+
+        // Outside of the starting camera's viewport the region stays where it was
+        if let Some(CameraCursor {
+            ui: Some(ui),
+            world: Some(world),
+            ..
+        }) = cursor
+        {
+            region.end = (*ui, *world);
+        }
     }
 }
 
@@ -103,9 +137,14 @@ fn draw_region(mut query: Query<(&RegionState, &mut Style), With<Region>>) {
     style.size = Size::new(Val::Px(width.abs()), Val::Px(height.abs()));
 }
 
+/// Selectables the `SpatialIndex` doesn't know about
+type Unindexed = (With<Selectable>, Without<Bounds2>);
+
 fn capture_region_confirmation(
     mut ev_confirmation: EventReader<ConfirmationEvent>,
-    query: Query<(Entity, &Transform), With<Selectable>>,
+    query: Query<&Transform, With<Selectable>>,
+    unindexed: Query<(Entity, &Transform), Unindexed>,
+    index: Res<SpatialIndex>,
     mut selected_entities: ResMut<SelectedEntities>,
 ) {
     if ev_confirmation.is_empty() {
@@ -115,17 +154,23 @@ fn capture_region_confirmation(
     let mut entities: Vec<Entity> = Vec::new();
 
     for ev in ev_confirmation.iter() {
-        let max = ev.start.max(ev.end);
-        let min = ev.start.min(ev.end);
+        let region = Bounds2::from_corners(ev.start, ev.end);
 
-        for (entity, transform) in query.into_iter() {
-            let position = transform.translation;
+        // Only entities with a `Bounds2` overlapping the region can have their center inside it
+        for entity in index.entities_in_rect(region.min(), region.max()) {
+            let transform = match query.get(entity) {
+                Ok(transform) => transform,
+                Err(_) => continue,
+            };
 
-            let in_x_axis = (max.x > position.x) && (min.x < position.x);
-            let in_y_axis = (max.y > position.y) && (min.y < position.y);
-            let in_area = in_x_axis && in_y_axis;
+            if region.in_bounds(transform.translation.truncate()) {
+                entities.push(entity);
+            }
+        }
 
-            if in_area {
+        // Selectables without a `Bounds2` aren't in the index, check them directly
+        for (entity, transform) in unindexed.iter() {
+            if region.in_bounds(transform.translation.truncate()) {
                 entities.push(entity);
             }
         }
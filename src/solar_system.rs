@@ -1,6 +1,10 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
-use crate::select_area::{Selectable, SelectedEntities};
+use crate::{
+    bounds::{Bounds2, Shape2, ShapeBounds2},
+    bounds_sync::SyncBounds,
+    select_area::{Selectable, SelectedEntities},
+};
 
 pub struct SolarSystemPlugin;
 
@@ -9,13 +13,11 @@ struct Planet {
     speed: f32,
 }
 
-#[derive(Component)]
-struct Name(String);
-
 #[derive(Component)]
 struct Sun;
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
 struct TimeScale(f32);
 
 #[derive(Component)]
@@ -27,6 +29,7 @@ struct TimeScaleIncrement(f32);
 impl Plugin for SolarSystemPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(TimeScale(1.0))
+            .register_type::<TimeScale>()
             .add_startup_system(setup_ui)
             .add_startup_system(spawn_sun)
             .add_startup_system(spawn_planets)
@@ -44,12 +47,15 @@ fn spawn_sun(
 ) {
     commands.spawn((
         Sun,
-        Name("Sun".to_string()),
+        Name::new("Sun"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(20.).into()).into(),
             material: materials.add(ColorMaterial::from(Color::YELLOW)),
             ..default()
         },
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(20.)),
+        SyncBounds,
     ));
 }
 
@@ -92,7 +98,7 @@ fn spawn_planets(
 
     // Mercury
     commands.spawn((
-        Name("Mercury".to_string()),
+        Name::new("Mercury"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(mercury_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::GRAY)),
@@ -103,11 +109,14 @@ fn spawn_planets(
             speed: mercury_speed,
         },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(mercury_radii)),
+        SyncBounds,
     ));
 
     // Venus
     commands.spawn((
-        Name("Venus".to_string()),
+        Name::new("Venus"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(venus_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::rgb_u8(178, 146, 95))),
@@ -116,11 +125,14 @@ fn spawn_planets(
         },
         Planet { speed: venus_speed },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(venus_radii)),
+        SyncBounds,
     ));
 
     // Earth
     commands.spawn((
-        Name("Earth".to_string()),
+        Name::new("Earth"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(earth_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::GREEN)),
@@ -129,11 +141,14 @@ fn spawn_planets(
         },
         Planet { speed: earth_speed },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(earth_radii)),
+        SyncBounds,
     ));
 
     // Mars
     commands.spawn((
-        Name("Mars".to_string()),
+        Name::new("Mars"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(mars_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::RED)),
@@ -142,11 +157,14 @@ fn spawn_planets(
         },
         Planet { speed: mars_speed },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(mars_radii)),
+        SyncBounds,
     ));
 
     // Jupiter
     commands.spawn((
-        Name("Jupiter".to_string()),
+        Name::new("Jupiter"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(jupiter_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::ORANGE_RED)),
@@ -157,11 +175,14 @@ fn spawn_planets(
             speed: jupiter_speed,
         },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(jupiter_radii)),
+        SyncBounds,
     ));
 
     // Saturn
     commands.spawn((
-        Name("Saturn".to_string()),
+        Name::new("Saturn"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(saturn_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::BEIGE)),
@@ -172,11 +193,14 @@ fn spawn_planets(
             speed: saturn_speed,
         },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(saturn_radii)),
+        SyncBounds,
     ));
 
     // Uranus
     commands.spawn((
-        Name("Uranus".to_string()),
+        Name::new("Uranus"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(uranus_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::SEA_GREEN)),
@@ -187,11 +211,14 @@ fn spawn_planets(
             speed: uranus_speed,
         },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(uranus_radii)),
+        SyncBounds,
     ));
 
     // Neptune
     commands.spawn((
-        Name("Neptune".to_string()),
+        Name::new("Neptune"),
         MaterialMesh2dBundle {
             mesh: meshes.add(shape::Circle::new(neptune_radii).into()).into(),
             material: materials.add(ColorMaterial::from(Color::BLUE)),
@@ -202,12 +229,17 @@ fn spawn_planets(
             speed: neptune_speed,
         },
         Selectable,
+        Bounds2::default(),
+        ShapeBounds2(Shape2::Circle(neptune_radii)),
+        SyncBounds,
     ));
 }
 
+type PlanetFilter = (Without<Sun>, With<Planet>);
+
 fn rotate_planets_around_sun(
     sun_query: Query<&Transform, (With<Sun>, Without<Planet>)>,
-    mut planets_query: Query<(&mut Transform, &Planet), (Without<Sun>, With<Planet>)>,
+    mut planets_query: Query<(&mut Transform, &Planet), PlanetFilter>,
     time: Res<Time>,
     time_scale: Res<TimeScale>,
 ) {
@@ -225,11 +257,14 @@ const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
+type TimeScaleButton<'a> = (
+    &'a Interaction,
+    &'a mut BackgroundColor,
+    &'a TimeScaleIncrement,
+);
+
 fn increment_timescale(
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor, &TimeScaleIncrement),
-        (Changed<Interaction>, With<Button>),
-    >,
+    mut interaction_query: Query<TimeScaleButton, (Changed<Interaction>, With<Button>)>,
     mut time_scale: ResMut<TimeScale>,
 ) {
     for (interaction, mut color, increment) in &mut interaction_query {
@@ -373,18 +408,20 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>, time_scale:
         });
 }
 
+type SelectablePlanet = (With<Planet>, With<Selectable>);
+
 fn handle_planet_selection(
-    query: Query<(Entity, &Name), (With<Planet>, With<Selectable>)>,
+    query: Query<(Entity, &Name), SelectablePlanet>,
     selected: Res<SelectedEntities>,
 ) {
     if selected.is_changed() {
-        if selected.0.len() == 0 {
+        if selected.0.is_empty() {
             return;
         }
 
         for (id, name) in query.into_iter() {
             if selected.0.contains(&id) {
-                println!("Selected: {}", name.0);
+                println!("Selected: {}", name);
             }
         }
     }
@@ -0,0 +1,404 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    bounds::{Bounds2, OrientedBounds2, ShapeBounds2},
+    bounds_sync::BoundsSyncSystemLabel,
+};
+
+/// Plugin that keeps a `SpatialIndex` of every entity with a `Bounds2`. Entities with only
+/// an `OrientedBounds2` or `ShapeBounds2` are indexed by the box around them
+pub struct SpatialIndexPlugin {
+    /// Size of a single grid cell in world units, ideally a bit bigger than
+    /// the typical entity so most entities only land in a few cells
+    pub cell_size: f32,
+}
+
+impl Default for SpatialIndexPlugin {
+    fn default() -> Self {
+        Self { cell_size: 128.0 }
+    }
+}
+
+/// Label to allow ordering against `SpatialIndexPlugin`
+#[derive(SystemLabel)]
+pub struct SpatialIndexSystemLabel;
+
+impl Plugin for SpatialIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpatialIndex::new(self.cell_size))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_spatial_index
+                    .label(SpatialIndexSystemLabel)
+                    .after(BoundsSyncSystemLabel),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_exact_bounds_index
+                    .label(SpatialIndexSystemLabel)
+                    .after(BoundsSyncSystemLabel)
+                    .after(update_spatial_index),
+            );
+    }
+}
+
+type ExactBounds<'a> = (
+    Entity,
+    &'a GlobalTransform,
+    Option<&'a OrientedBounds2>,
+    Option<&'a ShapeBounds2>,
+);
+
+type ExactBoundsChanged = (
+    Without<Bounds2>,
+    Or<(With<OrientedBounds2>, With<ShapeBounds2>)>,
+    Or<(
+        Changed<GlobalTransform>,
+        Changed<OrientedBounds2>,
+        Changed<ShapeBounds2>,
+    )>,
+);
+
+/// Uniform grid over world space, answers which `Bounds2` are under a point or
+/// overlap a rectangle without scanning every entity.
+#[derive(Resource, Debug)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<Entity>>,
+    entities: HashMap<Entity, (Vec2, Vec2)>,
+    /// Smallest and largest cell ever occupied, grows on insert but isn't shrunk on remove
+    extent: Option<(IVec2, IVec2)>,
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            entities: HashMap::default(),
+            extent: None,
+        }
+    }
+
+    /// Adds or moves an entity
+    pub fn insert(&mut self, entity: Entity, bounds: &Bounds2) {
+        self.remove(entity);
+
+        let min = bounds.min();
+        let max = bounds.max();
+
+        // Bounds from a degenerate transform would cover every cell
+        if !min.is_finite() || !max.is_finite() {
+            return;
+        }
+
+        for cell in self.cells_between(min, max) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+
+        let (min_cell, max_cell) = (self.cell(min), self.cell(max));
+        self.extent = Some(match self.extent {
+            Some((lo, hi)) => (lo.min(min_cell), hi.max(max_cell)),
+            None => (min_cell, max_cell),
+        });
+        self.entities.insert(entity, (min, max));
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let (min, max) = match self.entities.remove(&entity) {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        for cell in self.cells_between(min, max) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|e| *e != entity);
+
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Every entity whose bounds contain the point
+    pub fn entities_at_point(&self, point: Vec2) -> Vec<Entity> {
+        let cell = self.cell(point);
+
+        match self.cells.get(&cell) {
+            Some(entities) => entities
+                .iter()
+                .filter(|entity| {
+                    let (min, max) = self.entities[*entity];
+                    point.cmpge(min).all() && point.cmple(max).all()
+                })
+                .copied()
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Every entity whose bounds overlap the rectangle, the corners can be in any order
+    pub fn entities_in_rect(&self, a: Vec2, b: Vec2) -> Vec<Entity> {
+        let min = a.min(b);
+        let max = a.max(b);
+
+        if !min.is_finite() || !max.is_finite() {
+            return vec![];
+        }
+
+        // A huge rect would otherwise walk every cell it covers, not just the ones in use
+        let (lo, hi) = match self.extent {
+            Some(extent) => extent,
+            None => return vec![],
+        };
+        let min_cell = self.cell(min).max(lo);
+        let max_cell = self.cell(max).min(hi);
+
+        let mut seen: HashSet<Entity> = HashSet::default();
+        let mut found: Vec<Entity> = Vec::new();
+
+        for cell in cells_in(min_cell, max_cell) {
+            let entities = match self.cells.get(&cell) {
+                Some(entities) => entities,
+                None => continue,
+            };
+
+            for entity in entities {
+                if !seen.insert(*entity) {
+                    continue;
+                }
+
+                let (entity_min, entity_max) = self.entities[entity];
+                let overlaps = entity_min.cmple(max).all() && entity_max.cmpge(min).all();
+
+                if overlaps {
+                    found.push(*entity);
+                }
+            }
+        }
+
+        found
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    fn cell(&self, point: Vec2) -> IVec2 {
+        (point / self.cell_size).floor().as_ivec2()
+    }
+
+    fn cells_between(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = IVec2> {
+        cells_in(self.cell(min), self.cell(max))
+    }
+}
+
+/// Empty when `min` is past `max` on either axis
+fn cells_in(min: IVec2, max: IVec2) -> impl Iterator<Item = IVec2> {
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+}
+
+fn update_spatial_index(
+    query: Query<(Entity, &Bounds2), Changed<Bounds2>>,
+    removed: RemovedComponents<Bounds2>,
+    mut index: ResMut<SpatialIndex>,
+) {
+    for entity in removed.iter() {
+        index.remove(entity);
+    }
+
+    for (entity, bounds) in query.iter() {
+        index.insert(entity, bounds);
+    }
+}
+
+/// Entities without a `Bounds2` are indexed by the box around their exact bounds, which
+/// moves with the `GlobalTransform` instead of `Bounds2`
+fn update_exact_bounds_index(
+    query: Query<ExactBounds, ExactBoundsChanged>,
+    bounded: Query<(), With<Bounds2>>,
+    removed_oriented: RemovedComponents<OrientedBounds2>,
+    removed_shape: RemovedComponents<ShapeBounds2>,
+    mut index: ResMut<SpatialIndex>,
+) {
+    for entity in removed_oriented.iter().chain(removed_shape.iter()) {
+        // Still indexed through its `Bounds2`
+        if !bounded.contains(entity) {
+            index.remove(entity);
+        }
+    }
+
+    for (entity, transform, oriented, shape) in query.iter() {
+        // Same preference as picking, the shape is the more exact of the two
+        let aabb = match (shape, oriented) {
+            (Some(shape), _) => shape.aabb(transform),
+            (None, Some(oriented)) => oriented.aabb(transform),
+            (None, None) => continue,
+        };
+
+        index.insert(entity, &aabb);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(x: f32, y: f32, size: f32) -> Bounds2 {
+        Bounds2 {
+            position: Vec2::new(x, y),
+            size: Vec2::splat(size),
+        }
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugin(SpatialIndexPlugin { cell_size: 10.0 });
+        app
+    }
+
+    #[test]
+    fn finds_entities_across_cell_boundaries() {
+        let mut index = SpatialIndex::new(10.0);
+        let entity = Entity::from_raw(1);
+
+        // Straddles the four cells around the origin
+        index.insert(entity, &bounds(0.0, 0.0, 4.0));
+
+        for point in [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, 1.0),
+        ] {
+            assert_eq!(index.entities_at_point(point), vec![entity]);
+        }
+
+        assert!(index.entities_at_point(Vec2::new(3.0, 0.0)).is_empty());
+        assert_eq!(
+            index.entities_in_rect(Vec2::new(1.5, 1.5), Vec2::new(50.0, 50.0)),
+            vec![entity]
+        );
+        assert!(index
+            .entities_in_rect(Vec2::new(2.5, 2.5), Vec2::new(50.0, 50.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn moving_leaves_the_old_cells() {
+        let mut index = SpatialIndex::new(10.0);
+        let entity = Entity::from_raw(1);
+
+        index.insert(entity, &bounds(5.0, 5.0, 2.0));
+        index.insert(entity, &bounds(35.0, 5.0, 2.0));
+
+        assert!(index.entities_at_point(Vec2::new(5.0, 5.0)).is_empty());
+        assert_eq!(index.entities_at_point(Vec2::new(35.0, 5.0)), vec![entity]);
+        assert_eq!(index.len(), 1);
+
+        index.remove(entity);
+        assert!(index.is_empty());
+        assert!(index.cells.is_empty());
+    }
+
+    #[test]
+    fn rect_queries_stay_inside_the_occupied_extent() {
+        let mut index = SpatialIndex::new(10.0);
+        let entity = Entity::from_raw(1);
+
+        assert!(index
+            .entities_in_rect(Vec2::splat(-1e30), Vec2::splat(1e30))
+            .is_empty());
+
+        index.insert(entity, &bounds(5.0, 5.0, 2.0));
+        index.insert(Entity::from_raw(2), &bounds(-95.0, 45.0, 2.0));
+        assert_eq!(index.extent, Some((IVec2::new(-10, 0), IVec2::new(0, 4))));
+
+        // Huge or broken rects don't walk billions of cells
+        let mut found = index.entities_in_rect(Vec2::splat(-1e30), Vec2::splat(1e30));
+        found.sort();
+        assert_eq!(found, vec![entity, Entity::from_raw(2)]);
+        assert!(index
+            .entities_in_rect(Vec2::splat(f32::NAN), Vec2::splat(1.0))
+            .is_empty());
+        assert!(index
+            .entities_in_rect(Vec2::ZERO, Vec2::splat(f32::INFINITY))
+            .is_empty());
+    }
+
+    #[test]
+    fn follows_bounds_changes_and_despawns() {
+        let mut app = app();
+        let entity = app.world.spawn(bounds(5.0, 5.0, 2.0)).id();
+        app.update();
+
+        let index = app.world.resource::<SpatialIndex>();
+        assert_eq!(index.entities_at_point(Vec2::new(5.0, 5.0)), vec![entity]);
+
+        app.world.get_mut::<Bounds2>(entity).unwrap().position = Vec2::new(25.0, 5.0);
+        app.update();
+
+        let index = app.world.resource::<SpatialIndex>();
+        assert!(index.entities_at_point(Vec2::new(5.0, 5.0)).is_empty());
+        assert_eq!(index.entities_at_point(Vec2::new(25.0, 5.0)), vec![entity]);
+
+        app.world.despawn(entity);
+        app.update();
+
+        let index = app.world.resource::<SpatialIndex>();
+        assert!(index.entities_at_point(Vec2::new(25.0, 5.0)).is_empty());
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn indexes_exact_bounds_without_a_bounds2() {
+        let mut app = app();
+        let transform = GlobalTransform::from_xyz(20.0, 0.0, 0.0);
+        let oriented = app
+            .world
+            .spawn((transform, OrientedBounds2::new(Vec2::splat(4.0))))
+            .id();
+        let shape = app
+            .world
+            .spawn((
+                GlobalTransform::from_xyz(-20.0, 0.0, 0.0),
+                ShapeBounds2(crate::bounds::Shape2::Circle(3.0)),
+            ))
+            .id();
+        app.update();
+
+        let index = app.world.resource::<SpatialIndex>();
+        assert_eq!(
+            index.entities_at_point(Vec2::new(21.5, 1.5)),
+            vec![oriented]
+        );
+        assert_eq!(index.entities_at_point(Vec2::new(-22.5, 0.0)), vec![shape]);
+
+        // Follows the transform
+        *app.world.get_mut::<GlobalTransform>(oriented).unwrap() =
+            GlobalTransform::from_xyz(40.0, 0.0, 0.0);
+        app.update();
+
+        let index = app.world.resource::<SpatialIndex>();
+        assert!(index.entities_at_point(Vec2::new(20.0, 0.0)).is_empty());
+        assert_eq!(
+            index.entities_at_point(Vec2::new(40.0, 0.0)),
+            vec![oriented]
+        );
+
+        app.world.entity_mut(shape).remove::<ShapeBounds2>();
+        app.update();
+
+        let index = app.world.resource::<SpatialIndex>();
+        assert!(index.entities_at_point(Vec2::new(-20.0, 0.0)).is_empty());
+        assert_eq!(index.len(), 1);
+    }
+}
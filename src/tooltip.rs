@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use crate::{
+    cursor::MousePosition,
+    picking::{Picked, PickingSystemLabel},
+};
+
+/// Plugin that shows a tooltip next to the cursor for the topmost picked entity.
+/// Uses the `Tooltip` text if there is one, otherwise the entity's `Name`
+pub struct TooltipPlugin {
+    /// Seconds the cursor has to rest on an entity before the tooltip shows
+    pub delay: f32,
+}
+
+impl Default for TooltipPlugin {
+    fn default() -> Self {
+        Self { delay: 0.5 }
+    }
+}
+
+/// Custom tooltip text, takes priority over `Name`
+#[derive(Component, Debug, Clone)]
+pub struct Tooltip(pub String);
+
+/// Distance between the cursor and the tooltip, in pixels
+const CURSOR_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+/// Space kept between the tooltip and the window edges, in pixels
+const EDGE_MARGIN: f32 = 4.0;
+
+#[derive(Component)]
+struct TooltipNode;
+
+#[derive(Component)]
+struct TooltipText;
+
+#[derive(Resource)]
+struct TooltipState {
+    entity: Option<Entity>,
+    timer: Timer,
+}
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TooltipState {
+            entity: None,
+            timer: Timer::from_seconds(self.delay, TimerMode::Once),
+        })
+        .add_startup_system(setup_tooltip)
+        .add_system(update_tooltip.after(PickingSystemLabel))
+        .add_system(position_tooltip.after(update_tooltip));
+    }
+}
+
+fn setup_tooltip(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            TooltipNode,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                visibility: Visibility { is_visible: false },
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TooltipText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                    },
+                ),
+            ));
+        });
+}
+
+fn update_tooltip(
+    picked: Res<Picked>,
+    labels: Query<(Option<&Tooltip>, Option<&Name>)>,
+    time: Res<Time>,
+    mut state: ResMut<TooltipState>,
+    mut node_query: Query<&mut Visibility, With<TooltipNode>>,
+    mut text_query: Query<&mut Text, With<TooltipText>>,
+) {
+    let mut visibility = node_query.single_mut();
+
+    // Only entities with something to show count as hovered
+    let hovered = picked.topmost().and_then(|entity| {
+        let label = match labels.get(entity) {
+            Ok((Some(tooltip), _)) => tooltip.0.clone(),
+            Ok((None, Some(name))) => name.to_string(),
+            _ => return None,
+        };
+
+        Some((entity, label))
+    });
+
+    let (entity, label) = match hovered {
+        Some(hovered) => hovered,
+        None => {
+            state.entity = None;
+            visibility.is_visible = false;
+            return;
+        }
+    };
+
+    if state.entity != Some(entity) {
+        state.entity = Some(entity);
+        state.timer.reset();
+        visibility.is_visible = false;
+    }
+
+    state.timer.tick(time.delta());
+
+    if state.timer.finished() {
+        let mut text = text_query.single_mut();
+
+        if text.sections[0].value != label {
+            text.sections[0].value = label;
+        }
+
+        visibility.is_visible = true;
+    }
+}
+
+fn position_tooltip(
+    windows: Res<Windows>,
+    mouse_position: Res<MousePosition>,
+    mut query: Query<(&mut Style, &Node), With<TooltipNode>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+    let (mut style, node) = query.single_mut();
+    let size = node.size();
+
+    let mut position = mouse_position.cursor_ui + CURSOR_OFFSET;
+
+    // Flip to the other side of the cursor when it would go off screen
+    if position.x + size.x + EDGE_MARGIN > window_size.x {
+        position.x = mouse_position.cursor_ui.x - CURSOR_OFFSET.x - size.x;
+    }
+    if position.y + size.y + EDGE_MARGIN > window_size.y {
+        position.y = mouse_position.cursor_ui.y - CURSOR_OFFSET.y - size.y;
+    }
+
+    let max = (window_size - size - EDGE_MARGIN).max(Vec2::splat(EDGE_MARGIN));
+    let position = position.clamp(Vec2::splat(EDGE_MARGIN), max);
+
+    style.position.left = Val::Px(position.x);
+    style.position.top = Val::Px(position.y);
+}
@@ -0,0 +1,425 @@
+use bevy::{input::touch::Touch, prelude::*};
+
+use crate::cursor::CursorSystems;
+
+/// Plugin that turns `Touches` into gestures: tap, one finger pan, pinch and long press drag.
+///
+/// Taps are also sent as a mouse click so anything built on `WorldInteraction` works
+/// with touch, `PanCam` and `SelectAreaPlugin` read the `TouchGesture` resource.
+pub struct TouchPlugin {
+    /// How far a finger can move, in pixels, before it is no longer a tap or long press
+    pub tap_slop: f32,
+    /// Seconds a finger has to rest before it becomes a long press
+    pub long_press: f32,
+    /// Mouse button pressed and released for a tap
+    pub tap_button: MouseButton,
+}
+
+impl Default for TouchPlugin {
+    fn default() -> Self {
+        Self {
+            tap_slop: 10.0,
+            long_press: 0.5,
+            tap_button: MouseButton::Left,
+        }
+    }
+}
+
+/// Label to allow ordering against `TouchPlugin`
+#[derive(SystemLabel)]
+pub struct TouchSystemLabel;
+
+/// The gesture currently in progress, positions use the window's top left origin
+/// like UI `Style` positions
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub enum TouchGesture {
+    #[default]
+    None,
+    /// A finger is down but it hasn't moved or been held long enough to tell what it is
+    Pending,
+    /// One finger drag, `delta` is the movement this frame. Lifting a finger during a
+    /// pinch carries on as a pan with the other one
+    Pan { delta: Vec2 },
+    /// Two fingers, `ratio` is how much further apart they are compared to last frame
+    Pinch { center: Vec2, ratio: f32 },
+    /// Held still for `TouchPlugin::long_press` and then dragged
+    LongPressDrag { start: Vec2, current: Vec2 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TouchTap {
+    pub position: Vec2,
+}
+
+#[derive(Resource)]
+struct TouchSettings {
+    tap_slop: f32,
+    long_press: f64,
+    tap_button: MouseButton,
+}
+
+#[derive(Resource, Default)]
+struct TouchState {
+    pressed_at: f64,
+    pinch_distance: Option<f32>,
+    /// Where the only finger was last frame. `Touch::delta` keeps the last movement
+    /// until the finger moves again, so a resting finger would keep panning
+    last_position: Option<Vec2>,
+    release_tap_button: bool,
+}
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TouchSettings {
+            tap_slop: self.tap_slop,
+            long_press: self.long_press as f64,
+            tap_button: self.tap_button,
+        })
+        .init_resource::<TouchState>()
+        .init_resource::<TouchGesture>()
+        .add_event::<TouchTap>()
+        .add_system(
+            recognise_gestures
+                .label(TouchSystemLabel)
+                .before(CursorSystems::Track),
+        );
+    }
+}
+
+fn recognise_gestures(
+    touches: Res<Touches>,
+    time: Res<Time>,
+    settings: Res<TouchSettings>,
+    mut state: ResMut<TouchState>,
+    mut gesture: ResMut<TouchGesture>,
+    mut buttons: ResMut<Input<MouseButton>>,
+    mut ev_tap: EventWriter<TouchTap>,
+) {
+    // The tap's press was sent last frame, release it now so it reads as a click
+    if state.release_tap_button {
+        buttons.release(settings.tap_button);
+        state.release_tap_button = false;
+    }
+
+    let now = time.elapsed_seconds_f64();
+    let active: Vec<&Touch> = touches.iter().collect();
+    let last_position = state.last_position;
+    let moved = |touch: &Touch| match last_position {
+        Some(last) => touch.position() - last,
+        None => touch.distance(),
+    };
+
+    let next = match (active.as_slice(), *gesture) {
+        // A quick tap can start and end between two frames, so it might never be `Pending`
+        ([], TouchGesture::Pending | TouchGesture::None) => {
+            let released = touches.iter_just_released().next();
+
+            if let Some(touch) = released {
+                let is_quick =
+                    *gesture == TouchGesture::None || now - state.pressed_at < settings.long_press;
+
+                if is_quick && touch.distance().length() <= settings.tap_slop {
+                    ev_tap.send(TouchTap {
+                        position: touch.position(),
+                    });
+
+                    buttons.press(settings.tap_button);
+                    state.release_tap_button = true;
+                }
+            }
+
+            TouchGesture::None
+        }
+        ([], _) => TouchGesture::None,
+        ([touch], TouchGesture::None) => {
+            state.pressed_at = now;
+
+            if touch.distance().length() > settings.tap_slop {
+                TouchGesture::Pan {
+                    delta: moved(touch),
+                }
+            } else {
+                TouchGesture::Pending
+            }
+        }
+        ([touch], TouchGesture::Pending) => {
+            if touch.distance().length() > settings.tap_slop {
+                TouchGesture::Pan {
+                    delta: moved(touch),
+                }
+            } else if now - state.pressed_at >= settings.long_press {
+                TouchGesture::LongPressDrag {
+                    start: touch.start_position(),
+                    current: touch.position(),
+                }
+            } else {
+                TouchGesture::Pending
+            }
+        }
+        ([touch], TouchGesture::Pan { .. }) => TouchGesture::Pan {
+            delta: moved(touch),
+        },
+        ([touch], TouchGesture::LongPressDrag { start, .. }) => TouchGesture::LongPressDrag {
+            start,
+            current: touch.position(),
+        },
+        // The finger left behind picks up from where it is now
+        ([_], TouchGesture::Pinch { .. }) => TouchGesture::Pan { delta: Vec2::ZERO },
+        ([first, second, ..], _) => {
+            let distance = first.position().distance(second.position());
+            let ratio = match state.pinch_distance {
+                Some(previous) if previous > 0.0 => distance / previous,
+                _ => 1.0,
+            };
+
+            state.pinch_distance = Some(distance);
+
+            TouchGesture::Pinch {
+                center: (first.position() + second.position()) * 0.5,
+                ratio,
+            }
+        }
+    };
+
+    if !matches!(next, TouchGesture::Pinch { .. }) {
+        state.pinch_distance = None;
+    }
+
+    state.last_position = match active.as_slice() {
+        [touch] => Some(touch.position()),
+        _ => None,
+    };
+
+    if *gesture != next {
+        *gesture = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy::{
+        ecs::event::ManualEventReader,
+        input::{
+            touch::{TouchInput, TouchPhase},
+            InputPlugin,
+        },
+    };
+
+    use super::*;
+
+    struct TouchApp {
+        app: App,
+        startup: Instant,
+        taps: ManualEventReader<TouchTap>,
+    }
+
+    impl TouchApp {
+        fn new() -> Self {
+            let startup = Instant::now();
+            let mut app = App::new();
+
+            // `Time` is stepped by hand so long presses don't need a real wait
+            app.insert_resource(Time::new(startup))
+                .add_plugin(InputPlugin)
+                .add_plugin(TouchPlugin::default());
+
+            Self {
+                app,
+                startup,
+                taps: ManualEventReader::default(),
+            }
+        }
+
+        fn touch(&mut self, phase: TouchPhase, x: f32, y: f32) {
+            self.finger(0, phase, x, y);
+        }
+
+        fn finger(&mut self, id: u64, phase: TouchPhase, x: f32, y: f32) {
+            self.app.world.send_event(TouchInput {
+                phase,
+                position: Vec2::new(x, y),
+                force: None,
+                id,
+            });
+        }
+
+        fn update_at(&mut self, seconds: f32) {
+            let instant = self.startup + Duration::from_secs_f32(seconds);
+            self.app
+                .world
+                .resource_mut::<Time>()
+                .update_with_instant(instant);
+            self.app.update();
+        }
+
+        fn gesture(&self) -> TouchGesture {
+            *self.app.world.resource::<TouchGesture>()
+        }
+
+        fn taps(&mut self) -> Vec<Vec2> {
+            let events = self.app.world.resource::<Events<TouchTap>>();
+            self.taps.iter(events).map(|tap| tap.position).collect()
+        }
+    }
+
+    #[test]
+    fn quick_press_and_release_is_a_tap() {
+        let mut app = TouchApp::new();
+
+        app.touch(TouchPhase::Started, 10.0, 10.0);
+        app.update_at(0.0);
+        assert_eq!(app.gesture(), TouchGesture::Pending);
+
+        app.touch(TouchPhase::Ended, 10.0, 10.0);
+        app.update_at(0.1);
+        assert_eq!(app.gesture(), TouchGesture::None);
+        assert_eq!(app.taps(), vec![Vec2::new(10.0, 10.0)]);
+
+        let buttons = app.app.world.resource::<Input<MouseButton>>();
+        assert!(buttons.just_pressed(MouseButton::Left));
+    }
+
+    #[test]
+    fn holding_still_becomes_a_long_press_drag() {
+        let mut app = TouchApp::new();
+
+        app.touch(TouchPhase::Started, 10.0, 10.0);
+        app.update_at(0.0);
+        app.update_at(0.6);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::LongPressDrag {
+                start: Vec2::new(10.0, 10.0),
+                current: Vec2::new(10.0, 10.0),
+            }
+        );
+
+        app.touch(TouchPhase::Moved, 50.0, 10.0);
+        app.update_at(0.7);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::LongPressDrag {
+                start: Vec2::new(10.0, 10.0),
+                current: Vec2::new(50.0, 10.0),
+            }
+        );
+
+        // Releasing a long press isn't a tap
+        app.touch(TouchPhase::Ended, 50.0, 10.0);
+        app.update_at(0.8);
+        assert_eq!(app.gesture(), TouchGesture::None);
+        assert!(app.taps().is_empty());
+    }
+
+    #[test]
+    fn moving_past_the_slop_pans() {
+        let mut app = TouchApp::new();
+
+        app.touch(TouchPhase::Started, 0.0, 0.0);
+        app.update_at(0.0);
+
+        app.touch(TouchPhase::Moved, 40.0, 0.0);
+        app.update_at(0.1);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::Pan {
+                delta: Vec2::new(40.0, 0.0)
+            }
+        );
+
+        app.touch(TouchPhase::Moved, 45.0, 5.0);
+        app.update_at(0.2);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::Pan {
+                delta: Vec2::new(5.0, 5.0)
+            }
+        );
+
+        // Resting without a new `Moved` doesn't keep panning
+        app.update_at(0.25);
+        assert_eq!(app.gesture(), TouchGesture::Pan { delta: Vec2::ZERO });
+
+        app.touch(TouchPhase::Ended, 45.0, 5.0);
+        app.update_at(0.3);
+        assert_eq!(app.gesture(), TouchGesture::None);
+        assert!(app.taps().is_empty());
+    }
+
+    #[test]
+    fn two_fingers_pinch() {
+        let mut app = TouchApp::new();
+
+        app.finger(0, TouchPhase::Started, 0.0, 0.0);
+        app.finger(1, TouchPhase::Started, 100.0, 0.0);
+        app.update_at(0.0);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::Pinch {
+                center: Vec2::new(50.0, 0.0),
+                ratio: 1.0,
+            }
+        );
+
+        // Twice as far apart as last frame
+        app.finger(1, TouchPhase::Moved, 200.0, 0.0);
+        app.update_at(0.1);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::Pinch {
+                center: Vec2::new(100.0, 0.0),
+                ratio: 2.0,
+            }
+        );
+
+        app.finger(0, TouchPhase::Moved, 100.0, 0.0);
+        app.update_at(0.2);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::Pinch {
+                center: Vec2::new(150.0, 0.0),
+                ratio: 0.5,
+            }
+        );
+
+        // Nothing pinched is a tap either
+        app.finger(0, TouchPhase::Ended, 100.0, 0.0);
+        app.finger(1, TouchPhase::Ended, 200.0, 0.0);
+        app.update_at(0.3);
+        assert_eq!(app.gesture(), TouchGesture::None);
+        assert!(app.taps().is_empty());
+    }
+
+    #[test]
+    fn lifting_a_finger_mid_pinch_pans() {
+        let mut app = TouchApp::new();
+
+        app.finger(0, TouchPhase::Started, 0.0, 0.0);
+        app.finger(1, TouchPhase::Started, 100.0, 0.0);
+        app.update_at(0.0);
+        app.finger(1, TouchPhase::Moved, 150.0, 0.0);
+        app.update_at(0.1);
+        assert!(matches!(app.gesture(), TouchGesture::Pinch { .. }));
+
+        // The remaining finger starts from where it is, no jump
+        app.finger(1, TouchPhase::Ended, 150.0, 0.0);
+        app.update_at(0.2);
+        assert_eq!(app.gesture(), TouchGesture::Pan { delta: Vec2::ZERO });
+
+        app.finger(0, TouchPhase::Moved, 10.0, 5.0);
+        app.update_at(0.3);
+        assert_eq!(
+            app.gesture(),
+            TouchGesture::Pan {
+                delta: Vec2::new(10.0, 5.0)
+            }
+        );
+
+        app.finger(0, TouchPhase::Ended, 10.0, 5.0);
+        app.update_at(0.4);
+        assert_eq!(app.gesture(), TouchGesture::None);
+        assert!(app.taps().is_empty());
+    }
+}